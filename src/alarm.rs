@@ -0,0 +1,220 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::sound::{self, square_wave, wav_bytes, SAMPLE_RATE};
+
+/// Pause between repeats of a ringing alarm
+const REPEAT_GAP: Duration = Duration::from_millis(1500);
+
+// Note frequencies in Hz
+const E5: f32 = 659.3;
+const G5: f32 = 784.0;
+const A5: f32 = 880.0;
+const C6: f32 = 1046.5;
+const E6: f32 = 1318.5;
+const G6: f32 = 1568.0;
+const C7: f32 = 2093.0;
+/// A rest
+const REST: f32 = 0.0;
+
+/// Built-in 8-bit alarm tunes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Tune {
+    /// Three short beeps
+    Beep,
+    /// Rising arpeggio
+    #[default]
+    Chime,
+    /// Short victory fanfare
+    Fanfare,
+    /// Fast two-tone warble
+    Pager,
+}
+
+impl Tune {
+    /// Notes as (frequency, milliseconds); a frequency of 0 is a rest
+    fn notes(self) -> &'static [(f32, u32)] {
+        match self {
+            Tune::Beep => &[(A5, 150), (REST, 100), (A5, 150), (REST, 100), (A5, 150)],
+            Tune::Chime => &[(C6, 110), (E6, 110), (G6, 110), (C7, 320)],
+            Tune::Fanfare => &[
+                (G5, 100),
+                (REST, 30),
+                (G5, 100),
+                (REST, 30),
+                (G5, 100),
+                (REST, 30),
+                (C6, 260),
+                (E5, 130),
+                (C6, 400),
+            ],
+            Tune::Pager => &[(G6, 70), (E6, 70), (G6, 70), (E6, 70), (G6, 70), (E6, 70), (G6, 70), (E6, 70)],
+        }
+    }
+
+    /// The tune as mono samples at [`SAMPLE_RATE`]
+    pub fn samples(self, volume: f32) -> Vec<i16> {
+        self.notes()
+            .iter()
+            .flat_map(|&(frequency, millis)| {
+                if frequency == REST {
+                    vec![0; (SAMPLE_RATE * millis / 1000) as usize]
+                } else {
+                    square_wave(frequency, millis, volume)
+                }
+            })
+            .collect()
+    }
+
+    /// The tune as a WAV file
+    pub fn wav(self, volume: f32) -> Vec<u8> {
+        wav_bytes(&self.samples(volume))
+    }
+}
+
+/// What a finished timer plays
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlarmSound {
+    Tune(Tune),
+    /// A WAV or OGG file
+    File(PathBuf),
+}
+
+/// Short blip before a countdown ends
+pub fn tick() {
+    sound::beep(C7, 40);
+}
+
+/// Play a tune once at `volume`, blocking until done
+pub fn preview(tune: Tune, volume: f32) -> io::Result<()> {
+    let path = sound::temp_wav();
+    fs::write(&path, tune.wav(volume))?;
+    let result = sound::play_file(&path, 1.0, &AtomicBool::new(false));
+    let _ = fs::remove_file(&path);
+    result
+}
+
+/// The alarm of finished timers, played in the background until stopped
+#[derive(Debug, Default)]
+pub struct Alarm {
+    /// Set to stop the alarm; also set by the alarm once it is done
+    stop: Option<Arc<AtomicBool>>,
+}
+
+impl Alarm {
+    pub fn is_ringing(&self) -> bool {
+        self.stop.as_ref().is_some_and(|stop| !stop.load(Ordering::Relaxed))
+    }
+
+    /// Start playing `sound`, over and over with `repeat`; does nothing while
+    /// already ringing or with sounds off
+    pub fn ring(&mut self, sound: AlarmSound, repeat: bool) {
+        if self.is_ringing() || !sound::enabled() {
+            return;
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        self.stop = Some(stop.clone());
+        let volume = sound::volume();
+
+        thread::spawn(move || {
+            let (path, player_volume, temporary) = match sound {
+                AlarmSound::Tune(tune) => {
+                    let path = sound::temp_wav();
+                    if let Err(err) = fs::write(&path, tune.wav(volume)) {
+                        eprintln!("tiemrzzzz: couldn't play the alarm: {err}");
+                        stop.store(true, Ordering::Relaxed);
+                        return;
+                    }
+                    (path, 1.0, true)
+                }
+                AlarmSound::File(path) => (path, volume, false),
+            };
+            loop {
+                if let Err(err) = sound::play_file(&path, player_volume, &stop) {
+                    eprintln!("tiemrzzzz: couldn't play {}: {err}", path.display());
+                    break;
+                }
+                if !repeat || wait(REPEAT_GAP, &stop) {
+                    break;
+                }
+            }
+            stop.store(true, Ordering::Relaxed);
+            if temporary {
+                let _ = fs::remove_file(&path);
+            }
+        });
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            stop.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Drop for Alarm {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Sleep for `duration`; returns early with true once `stop` is set
+fn wait(duration: Duration, stop: &AtomicBool) -> bool {
+    let until = Instant::now() + duration;
+    while Instant::now() < until {
+        if stop.load(Ordering::Relaxed) {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    stop.load(Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tunes_render_their_notes() {
+        for tune in Tune::value_variants() {
+            let millis: u32 = tune.notes().iter().map(|&(_, millis)| millis).sum();
+            let samples = tune.samples(0.5);
+            assert!(samples.len().abs_diff((SAMPLE_RATE * millis / 1000) as usize) < tune.notes().len());
+            assert!(samples.iter().any(|&sample| sample != 0));
+        }
+        assert_eq!(Tune::Beep.samples(0.0).iter().max(), Some(&0));
+    }
+
+    #[test]
+    fn renders_wav_files() {
+        let wav = Tune::Chime.wav(0.3);
+        let data_len = u32::from_le_bytes(wav[40..44].try_into().unwrap()) as usize;
+        assert_eq!(data_len, Tune::Chime.samples(0.3).len() * 2);
+        assert_eq!(wav.len(), 44 + data_len);
+    }
+
+    #[test]
+    fn parses_tune_names() {
+        let tune: Tune = toml::Value::String("fanfare".to_owned()).try_into().unwrap();
+        assert_eq!(tune, Tune::Fanfare);
+        assert!(Tune::from_str("pager", true).is_ok());
+    }
+
+    #[test]
+    fn stopping_ends_the_wait() {
+        let stop = AtomicBool::new(true);
+        let start = Instant::now();
+        assert!(wait(Duration::from_secs(10), &stop));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(!Alarm::default().is_ringing());
+    }
+}
@@ -1,30 +1,779 @@
-use eframe::egui::{self, Color32, Pos2, RichText, Vec2};
+use std::io;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
 
-use crate::display::draw_time;
-use crate::timer::{Timer, TimerState};
+use eframe::egui::{self, Color32, FontId, Pos2, RichText, Vec2};
+
+use crate::alarm::{self, Alarm};
+use crate::cli::Args;
+use crate::config::{Config, ConfigFile, Seconds};
+use crate::control::{self, Command, ControlServer, Launch, Remote, Request, Status};
+use crate::dbus::DbusService;
+use crate::display::{draw_time, Blink, TimeParts};
+use crate::keymap::{Shortcut, DIGIT_KEYS};
+use crate::keypad::{take_typing_keys, TimeEntry, TypingKey};
+use crate::laps::format_lap_time;
+use crate::notify::{self, Notifier};
+use crate::pomodoro::PomodoroConfig;
+use crate::presets::{Preset, Presets};
+use crate::sequence::{Segment, Sequence, SequenceConfig};
+use crate::session::{Session, WindowState};
+use crate::sound;
+use crate::timer::{Timer, TimerEvent, TimerKind, TimerState};
+use crate::timers::{Mode, NamedTimer, TimerList};
+
+/// Window size of the full UI
+const FULL_SIZE: [f32; 2] = [400.0, 400.0];
+
+/// How often the config file is checked for changes
+const CONFIG_CHECK: Duration = Duration::from_secs(1);
+
+/// Least time between writes of the session file
+const SESSION_SAVE: Duration = Duration::from_secs(1);
+
+/// A preset being created (`index` is `None`) or edited
+struct PresetEdit {
+    index: Option<usize>,
+    name: String,
+    seconds: u32,
+}
 
 /// Main application state
 pub struct TimerApp {
-    timer: Timer,
+    timers: TimerList,
     show_colon: bool,
     colon_timer: f32,
     overlay_mode: bool,
+    /// Pomodoro settings for newly started Pomodoro timers
+    pomodoro_config: PomodoroConfig,
+    /// Interval settings for newly started interval timers
+    sequence_config: SequenceConfig,
+    show_sequence_settings: bool,
+    /// Timer being renamed and the name being typed
+    renaming: Option<(usize, String)>,
+    presets: Presets,
+    presets_path: Option<PathBuf>,
+    /// Last preset load/save error
+    preset_status: Option<String>,
+    editing_preset: Option<PresetEdit>,
+    /// Commands from the control socket and D-Bus
+    requests: Option<mpsc::Receiver<Request>>,
+    /// Kept so the control socket stays open until the app exits
+    _control: Option<ControlServer>,
+    /// Timer object on the session bus
+    dbus: Option<DbusService>,
+    config: Config,
+    /// Watched for changes; `None` without a config directory
+    config_file: Option<ConfigFile>,
+    /// Last config load error
+    config_status: Option<String>,
+    next_config_check: Instant,
+    /// Where timers are saved for the next launch; `None` without a state directory
+    session_path: Option<PathBuf>,
+    /// Last session written, to save only changes
+    saved_session: Option<Session>,
+    window: WindowState,
+    next_session_save: Instant,
+    /// Rings while a finished message is waiting to be dismissed
+    alarm: Alarm,
+    /// Desktop notifications; `None` without a notification server
+    notifier: Option<Notifier>,
+    /// Time being typed for the primary countdown
+    time_entry: Option<TimeEntry>,
+    show_help: bool,
 }
 
 impl Default for TimerApp {
     fn default() -> Self {
         Self {
-            timer: Timer::default(),
+            timers: TimerList::default(),
             show_colon: true,
             colon_timer: 0.0,
             overlay_mode: false,
+            pomodoro_config: PomodoroConfig::default(),
+            sequence_config: SequenceConfig::default(),
+            show_sequence_settings: false,
+            renaming: None,
+            presets: Presets::default(),
+            presets_path: None,
+            preset_status: None,
+            editing_preset: None,
+            requests: None,
+            _control: None,
+            dbus: None,
+            config: Config::default(),
+            config_file: None,
+            config_status: None,
+            next_config_check: Instant::now(),
+            session_path: None,
+            saved_session: None,
+            window: WindowState::default(),
+            next_session_save: Instant::now(),
+            alarm: Alarm::default(),
+            notifier: None,
+            time_entry: None,
+            show_help: false,
         }
     }
 }
 
 impl TimerApp {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        Self::default()
+    pub fn new(
+        cc: &eframe::CreationContext<'_>,
+        args: Args,
+        config_file: Option<ConfigFile>,
+        config: io::Result<Config>,
+        session: Option<Session>,
+    ) -> Self {
+        let ctx = cc.egui_ctx.clone();
+        let (remote, requests) = Remote::new(move || ctx.request_repaint());
+        let control = ControlServer::listen(&control::socket_path(), remote.clone())
+            .map_err(|err| eprintln!("tiemrzzzz: control socket disabled: {err}"))
+            .ok();
+        let dbus = DbusService::start(remote)
+            .map_err(|err| eprintln!("tiemrzzzz: D-Bus service disabled: {err}"))
+            .ok();
+        let ctx = cc.egui_ctx.clone();
+        let notifier = Notifier::start(move || ctx.request_repaint())
+            .map_err(|err| eprintln!("tiemrzzzz: desktop notifications disabled: {err}"))
+            .ok();
+
+        let window = session.as_ref().map(|session| session.window).unwrap_or_default();
+        let mut app = Self {
+            overlay_mode: args.overlay || window.overlay,
+            presets_path: Presets::default_path(),
+            requests: Some(requests),
+            _control: control,
+            dbus,
+            notifier,
+            session_path: Session::default_path(),
+            saved_session: session.clone(),
+            window,
+            ..Self::default()
+        };
+        if let Some(path) = &app.presets_path {
+            match Presets::load(path) {
+                Ok(presets) => app.presets = presets,
+                Err(err) => app.preset_status = Some(format!("Couldn't load presets: {err}")),
+            }
+        }
+
+        app.config_file = config_file;
+        let config = app.check_config(config);
+        app.timers = match &session {
+            Some(session) => session.restore(config.defaults.timer()),
+            None => TimerList::new(config.defaults.timer()),
+        };
+        app.apply_config(&cc.egui_ctx, config);
+        // A timer that was ringing when the app closed rings again
+        if app.timers.iter().any(|entry| entry.finished_notice.is_some()) {
+            app.alarm.ring(app.config.sounds.alarm(), app.config.sounds.repeat);
+        }
+
+        match Launch::from_args(&args, &app.presets) {
+            Ok(launch) => {
+                // Leave restored timers alone and set up the requested one next to them
+                if session.is_some() && launch.configures_timer() {
+                    app.timers.add();
+                }
+                launch.apply(app.timers.primary_mut());
+            }
+            Err(err) => app.preset_status = Some(err),
+        }
+        app
+    }
+
+    /// Write the timers and window to the session file when they changed;
+    /// returns how long a change held back by [`SESSION_SAVE`] has to wait
+    fn save_session(&mut self) -> Option<Duration> {
+        let path = self.session_path.as_ref()?;
+        let session = Session::capture(&self.timers, self.window);
+        if self.saved_session.as_ref().is_some_and(|saved| saved.matches(&session)) {
+            return None;
+        }
+        let now = Instant::now();
+        if now < self.next_session_save {
+            return Some(self.next_session_save - now);
+        }
+        self.next_session_save = now + SESSION_SAVE;
+        if let Err(err) = session.save(path) {
+            eprintln!("tiemrzzzz: couldn't save {}: {err}", path.display());
+        }
+        self.saved_session = Some(session);
+        None
+    }
+
+    /// Run keyboard shortcuts; while a time is being typed the keys type it instead
+    fn handle_keys(&mut self, ctx: &egui::Context) {
+        let key = |key: egui::Key| ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, key));
+        if self.time_entry.is_some() {
+            for key in ctx.input_mut(|i| take_typing_keys(&mut i.events)) {
+                let Some(entry) = &mut self.time_entry else {
+                    break;
+                };
+                match key {
+                    TypingKey::Digit(digit) => entry.push(digit),
+                    TypingKey::Backspace => entry.pop(),
+                    TypingKey::Enter => {
+                        if !entry.is_empty() {
+                            let seconds = entry.seconds();
+                            self.timers.primary_mut().set_countdown(seconds);
+                        }
+                        self.time_entry = None;
+                    }
+                    TypingKey::Escape => self.time_entry = None,
+                }
+            }
+            return;
+        }
+        if self.show_help && key(egui::Key::Escape) {
+            self.show_help = false;
+        }
+
+        for shortcut in ctx.input_mut(|i| self.config.shortcuts.pressed(i)) {
+            self.run_shortcut(ctx, shortcut);
+        }
+        if self.config.shortcuts.preset_keys {
+            if let Some(index) = DIGIT_KEYS[1..].iter().position(|&digit| key(digit)) {
+                self.load_preset(index);
+            }
+        }
+    }
+
+    fn run_shortcut(&mut self, ctx: &egui::Context, shortcut: Shortcut) {
+        let primary = self.timers.primary_mut();
+        match shortcut {
+            // Like OK on the finished message when there is one
+            Shortcut::Toggle if primary.finished_notice.is_some() => primary.dismiss(),
+            Shortcut::Toggle => primary.timer.toggle(),
+            Shortcut::Reset => primary.reset(),
+            Shortcut::Overlay => self.set_overlay(ctx, !self.overlay_mode),
+            Shortcut::Lap => primary.record_lap(),
+            Shortcut::EditTime => {
+                if self.can_type_time() {
+                    self.time_entry = Some(TimeEntry::default());
+                }
+            }
+            Shortcut::Help => self.show_help = !self.show_help,
+            Shortcut::Quit => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+            adjust => {
+                let seconds = adjust.adjustment().unwrap_or_default();
+                match primary.timer.state {
+                    TimerState::Stopped if primary.mode() == Mode::Countdown => primary.timer.add_seconds(seconds),
+                    // Underway: change what is left, as the extend buttons do
+                    TimerState::Running | TimerState::Paused if primary.sequence.is_none() => primary.extend(seconds),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Whether a time can be typed into the display: the primary timer is a stopped countdown
+    fn can_type_time(&self) -> bool {
+        let primary = self.timers.primary();
+        primary.mode() == Mode::Countdown && primary.timer.state == TimerState::Stopped && !self.overlay_mode
+    }
+
+    /// Window listing the keyboard shortcuts
+    fn draw_help(&mut self, ctx: &egui::Context) {
+        let keymap = &self.config.shortcuts;
+        egui::Window::new("Keyboard shortcuts")
+            .open(&mut self.show_help)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 40.0])
+            .show(ctx, |ui| {
+                egui::Grid::new("shortcut_list").num_columns(2).spacing([16.0, 3.0]).show(ui, |ui| {
+                    let mut row = |key: &str, description: &str| {
+                        ui.label(RichText::new(key).monospace());
+                        ui.label(description);
+                        ui.end_row();
+                    };
+                    for shortcut in Shortcut::ALL {
+                        row(&keymap.key(shortcut).to_string(), shortcut.description());
+                    }
+                    if keymap.preset_keys {
+                        row("1-9", "Load a preset");
+                    }
+                    row("0-9", "Digits of a typed time");
+                    row("Enter / Escape", "Set or cancel a typed time");
+                });
+                ui.add_space(4.0);
+                ui.label(RichText::new("Rebind them in the [shortcuts] table of config.toml").size(11.0));
+            });
+    }
+
+    /// Carry out what was picked on desktop notifications
+    fn run_notification_actions(&mut self, ctx: &egui::Context) {
+        let Some(notifier) = &mut self.notifier else {
+            return;
+        };
+        for (id, action) in notifier.actions() {
+            let Some(entry) = self.timers.find_mut(id) else {
+                continue;
+            };
+            match action {
+                notify::Action::Restart => entry.restart(),
+                notify::Action::Snooze(seconds) => entry.snooze(seconds),
+                notify::Action::Dismiss => entry.dismiss(),
+                notify::Action::Show => {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                }
+            }
+        }
+    }
+
+    /// Announce finished timers and phase changes on the desktop, and withdraw
+    /// finished notifications once dismissed here
+    fn post_notifications(&mut self, events: &[(usize, TimerEvent)]) {
+        let Some(notifier) = &mut self.notifier else {
+            return;
+        };
+        if self.config.notifications.enabled {
+            for &(index, event) in events {
+                let Some(entry) = self.timers.get(index) else {
+                    continue;
+                };
+                if let Err(err) = notifier.announce(entry, event) {
+                    eprintln!("tiemrzzzz: couldn't post a notification: {err}");
+                }
+            }
+        }
+        notifier.close_dismissed(&self.timers);
+    }
+
+    /// Remember where the window is and how big the full UI is
+    fn track_window(&mut self, ctx: &egui::Context) {
+        let (outer, inner) = ctx.input(|i| (i.viewport().outer_rect, i.viewport().inner_rect));
+        if let Some(outer) = outer {
+            self.window.position = Some(outer.min.into());
+        }
+        if let (Some(inner), false) = (inner, self.overlay_mode) {
+            self.window.size = Some(inner.size().into());
+        }
+        self.window.overlay = self.overlay_mode;
+    }
+
+    /// Switch between the full UI and the small always-on-top overlay
+    fn set_overlay(&mut self, ctx: &egui::Context, overlay: bool) {
+        self.overlay_mode = overlay;
+        if overlay {
+            // Make window small, always on top, no decorations, transparent
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(self.config.overlay.size().into()));
+            ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(egui::WindowLevel::AlwaysOnTop));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Transparent(true));
+        } else {
+            // Request window resize back to normal
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(FULL_SIZE.into()));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(true));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Transparent(false));
+        }
+    }
+
+    /// Record the outcome of loading the config, keeping the current one on error
+    fn check_config(&mut self, config: io::Result<Config>) -> Config {
+        match config {
+            Ok(config) => {
+                self.config_status = None;
+                config
+            }
+            Err(err) => {
+                let path = self.config_file.as_ref().map(|file| file.path().display().to_string()).unwrap_or_default();
+                eprintln!("tiemrzzzz: couldn't load {path}: {err}");
+                self.config_status = Some(format!("Config error, using previous settings: {err}"));
+                self.config.clone()
+            }
+        }
+    }
+
+    /// Switch to a new config; pomodoro settings are only replaced when the file changed them
+    fn apply_config(&mut self, ctx: &egui::Context, config: Config) {
+        if config.defaults.pomodoro != self.config.defaults.pomodoro {
+            self.pomodoro_config = config.defaults.pomodoro.config();
+        }
+        self.timers.set_defaults(config.defaults.timer());
+        sound::configure(config.sounds.enabled, config.sounds.volume);
+        if let Some(notifier) = &mut self.notifier {
+            notifier.set_snooze(config.defaults.snooze.first().map(|&Seconds(seconds)| seconds));
+        }
+        let resize_overlay = self.overlay_mode && config.overlay.size() != self.config.overlay.size();
+        self.config = config;
+        if resize_overlay {
+            self.set_overlay(ctx, true);
+        }
+    }
+
+    /// Pick up edits to the config file
+    fn reload_config(&mut self, ctx: &egui::Context) {
+        if Instant::now() < self.next_config_check {
+            return;
+        }
+        self.next_config_check = Instant::now() + CONFIG_CHECK;
+        if let Some(config) = self.config_file.as_mut().and_then(ConfigFile::reload) {
+            let config = self.check_config(config);
+            self.apply_config(ctx, config);
+        }
+    }
+
+    fn load_preset(&mut self, index: usize) {
+        if let Some(preset) = self.presets.get(index).cloned() {
+            self.timers.primary_mut().load_preset(&preset);
+        }
+    }
+
+    fn save_presets(&mut self) {
+        let Some(path) = &self.presets_path else {
+            return;
+        };
+        self.preset_status = self.presets.save(path).err().map(|err| format!("Couldn't save presets: {err}"));
+    }
+
+    /// Row of preset buttons with a button to save the current time as a new preset
+    fn draw_preset_bar(&mut self, ui: &mut egui::Ui, text_color: Color32, button_color: Color32) {
+        let mut load = None;
+        let mut edit = None;
+        let mut remove = None;
+
+        ui.horizontal_wrapped(|ui| {
+            for (i, preset) in self.presets.as_slice().iter().enumerate() {
+                let text = match i {
+                    0..=8 => format!("{} {}", i + 1, preset.label()),
+                    _ => preset.label(),
+                };
+                let response = ui
+                    .add(egui::Button::new(RichText::new(text).size(12.0).color(text_color)).fill(Color32::TRANSPARENT))
+                    .on_hover_cursor(egui::CursorIcon::PointingHand);
+                if response.clicked() {
+                    load = Some(i);
+                }
+                response.context_menu(|ui| {
+                    if ui.button("Edit…").clicked() {
+                        edit = Some(i);
+                        ui.close_menu();
+                    }
+                    if ui.button("Delete").clicked() {
+                        remove = Some(i);
+                        ui.close_menu();
+                    }
+                });
+            }
+
+            if ui.button(RichText::new("+ Save").size(12.0).color(button_color)).on_hover_text("Save the current time as a preset").clicked() {
+                let timer = &self.timers.primary().timer;
+                let seconds = if timer.kind == TimerKind::Countdown { timer.duration } else { 5 * 60 };
+                self.editing_preset = Some(PresetEdit { index: None, name: String::new(), seconds });
+            }
+        });
+
+        if let Some(status) = &self.preset_status {
+            ui.label(RichText::new(status).size(11.0).color(text_color));
+        }
+
+        if let Some(i) = load {
+            self.load_preset(i);
+        }
+        if let Some((index, preset)) = edit.and_then(|i| self.presets.get(i).map(|p| (i, p.clone()))) {
+            self.editing_preset = Some(PresetEdit { index: Some(index), name: preset.name, seconds: preset.seconds });
+        }
+        if let Some(i) = remove {
+            self.presets.remove(i);
+            self.save_presets();
+        }
+    }
+
+    /// Window for creating or editing a preset
+    fn draw_preset_editor(&mut self, ctx: &egui::Context) {
+        let Some(edit) = self.editing_preset.as_mut() else {
+            return;
+        };
+        let mut save = false;
+        let mut delete = false;
+        let mut close = false;
+
+        let title = if edit.index.is_some() { "Edit preset" } else { "New preset" };
+        egui::Window::new(title)
+            .id(egui::Id::new("preset_editor"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 40.0])
+            .show(ctx, |ui| {
+                egui::Grid::new("preset_fields").num_columns(2).spacing([8.0, 4.0]).show(ui, |ui| {
+                    ui.label("Name");
+                    ui.text_edit_singleline(&mut edit.name);
+                    ui.end_row();
+
+                    let (mut hours, mut minutes, mut seconds) = (edit.seconds / 3600, (edit.seconds / 60) % 60, edit.seconds % 60);
+                    ui.label("Time");
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut hours).range(0..=99).suffix(" h"));
+                        ui.add(egui::DragValue::new(&mut minutes).range(0..=59).suffix(" m"));
+                        ui.add(egui::DragValue::new(&mut seconds).range(0..=59).suffix(" s"));
+                    });
+                    edit.seconds = hours * 3600 + minutes * 60 + seconds;
+                    ui.end_row();
+                });
+
+                ui.horizontal(|ui| {
+                    let valid = !edit.name.trim().is_empty() && edit.seconds > 0;
+                    save = ui.add_enabled(valid, egui::Button::new("Save")).clicked();
+                    close = ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(egui::Key::Escape));
+                    if edit.index.is_some() {
+                        delete = ui.button("Delete").clicked();
+                    }
+                });
+            });
+
+        let preset = Preset::new(&edit.name, edit.seconds);
+        match edit.index {
+            Some(index) if save => self.presets.update(index, preset),
+            Some(index) if delete => self.presets.remove(index),
+            None if save => self.presets.add(preset),
+            _ => {}
+        }
+        if save || delete {
+            self.save_presets();
+        }
+        if save || delete || close {
+            self.editing_preset = None;
+        }
+    }
+
+    fn set_mode(&mut self, mode: Mode) {
+        self.show_sequence_settings = false;
+        self.timers
+            .primary_mut()
+            .set_mode(mode, &self.pomodoro_config, &self.sequence_config);
+    }
+
+    /// Update a Pomodoro setting and reload the current phase if it is not underway
+    fn adjust_pomodoro(&mut self, adjust: impl FnOnce(&mut PomodoroConfig)) {
+        adjust(&mut self.pomodoro_config);
+        let entry = self.timers.primary_mut();
+        if let Some(pomodoro) = entry.pomodoro.as_mut() {
+            pomodoro.config = self.pomodoro_config.clone();
+            if entry.timer.state == TimerState::Stopped {
+                pomodoro.apply(&mut entry.timer);
+            }
+        }
+    }
+
+    /// Interval settings window; changes apply once the sequence is stopped
+    fn draw_sequence_settings(&mut self, ctx: &egui::Context) {
+        let mut config = self.sequence_config.clone();
+        let mut open = self.show_sequence_settings;
+
+        egui::Window::new("Interval settings")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("sequence_settings").num_columns(3).spacing([8.0, 4.0]).show(ui, |ui| {
+                    for (label, slot, default_name) in [("Warm-up", &mut config.warmup, "Warm-up"), ("Cool-down", &mut config.cooldown, "Cool-down")] {
+                        let mut enabled = slot.is_some();
+                        if ui.checkbox(&mut enabled, label).changed() {
+                            *slot = enabled.then(|| Segment::new(default_name, 60));
+                        }
+                        match slot {
+                            Some(segment) => {
+                                ui.text_edit_singleline(&mut segment.name);
+                                ui.add(egui::DragValue::new(&mut segment.seconds).range(1..=3600).suffix(" s"));
+                            }
+                            None => {
+                                ui.label("");
+                                ui.label("");
+                            }
+                        }
+                        ui.end_row();
+                    }
+
+                    let mut remove = None;
+                    for (i, segment) in config.segments.iter_mut().enumerate() {
+                        if ui.small_button("✕").on_hover_text("Remove segment").clicked() {
+                            remove = Some(i);
+                        }
+                        ui.text_edit_singleline(&mut segment.name);
+                        ui.add(egui::DragValue::new(&mut segment.seconds).range(1..=3600).suffix(" s"));
+                        ui.end_row();
+                    }
+                    if let Some(i) = remove {
+                        config.segments.remove(i);
+                    }
+
+                    if ui.small_button("+ Segment").clicked() {
+                        config.segments.push(Segment::new("Work", 30));
+                    }
+                    ui.end_row();
+
+                    ui.label("Rounds");
+                    ui.add(egui::DragValue::new(&mut config.rounds).range(1..=99));
+                    ui.end_row();
+                });
+            });
+
+        self.show_sequence_settings = open;
+        if config != self.sequence_config {
+            self.sequence_config = config.clone();
+            let entry = self.timers.primary_mut();
+            if let Some(sequence) = entry.sequence.as_mut() {
+                sequence.config = config;
+                if entry.timer.state == TimerState::Stopped {
+                    entry.reset();
+                }
+            }
+        }
+    }
+
+    /// Compact list of all timers; click to show one large, right-click for more
+    fn draw_timer_list(&mut self, ui: &mut egui::Ui, text_color: Color32, button_color: Color32) {
+        let primary = self.timers.primary_index();
+        let count = self.timers.len();
+        let mut select = None;
+        let mut remove = None;
+        let mut reorder = None;
+
+        ui.horizontal_wrapped(|ui| {
+            for (i, entry) in self.timers.iter_mut().enumerate() {
+                let icon = match entry.timer.state {
+                    _ if entry.finished_notice.is_some() => "⏰",
+                    TimerState::Running => "▶",
+                    TimerState::Paused => "⏸",
+                    TimerState::Stopped => "■",
+                };
+                let text = RichText::new(format!("{icon} {} {}", entry.name, short_time(&entry.timer)))
+                    .size(13.0)
+                    .color(if i == primary { Color32::WHITE } else { text_color });
+                let chip = egui::Button::new(text).fill(if i == primary { button_color } else { Color32::TRANSPARENT });
+
+                let response = ui.add(chip).on_hover_cursor(egui::CursorIcon::PointingHand);
+                if response.clicked() {
+                    select = Some(i);
+                }
+                if response.double_clicked() {
+                    self.renaming = Some((i, entry.name.clone()));
+                }
+                response.context_menu(|ui| {
+                    if ui.button(if entry.timer.is_running() { "Pause" } else { "Start" }).clicked() {
+                        entry.timer.toggle();
+                        ui.close_menu();
+                    }
+                    if ui.button("Rename…").clicked() {
+                        self.renaming = Some((i, entry.name.clone()));
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(i > 0, egui::Button::new("Move left")).clicked() {
+                        reorder = Some((i, i - 1));
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(i + 1 < count, egui::Button::new("Move right")).clicked() {
+                        reorder = Some((i, i + 1));
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(count > 1, egui::Button::new("Remove")).clicked() {
+                        remove = Some(i);
+                        ui.close_menu();
+                    }
+                });
+            }
+
+            if ui.button(RichText::new("+").size(13.0).color(button_color)).on_hover_text("Add a timer").clicked() {
+                self.timers.add();
+            }
+            let help = format!("Keyboard shortcuts ({})", self.config.shortcuts.help);
+            if ui.button(RichText::new("?").size(13.0).color(button_color)).on_hover_text(help).clicked() {
+                self.show_help = !self.show_help;
+            }
+        });
+
+        if let Some(i) = select {
+            self.timers.select(i);
+        }
+        if let Some((from, to)) = reorder {
+            self.timers.move_timer(from, to);
+        }
+        if let Some(i) = remove {
+            self.timers.remove(i);
+        }
+    }
+
+    /// Small window for typing a new timer name
+    fn draw_rename(&mut self, ctx: &egui::Context) {
+        let Some((index, name)) = self.renaming.as_mut() else {
+            return;
+        };
+        let mut done = false;
+        let mut apply = false;
+
+        egui::Window::new("Rename timer")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 40.0])
+            .show(ctx, |ui| {
+                let response = ui.text_edit_singleline(name);
+                response.request_focus();
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    apply = true;
+                }
+                ui.horizontal(|ui| {
+                    apply |= ui.button("OK").clicked();
+                    done |= ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(egui::Key::Escape));
+                });
+            });
+
+        if apply {
+            let (index, name) = (*index, name.clone());
+            self.timers.rename(index, &name);
+        }
+        if apply || done {
+            self.renaming = None;
+        }
+    }
+
+    /// Draw the lap table (newest first) with best/worst highlighting
+    fn draw_laps(&mut self, ui: &mut egui::Ui, rect: egui::Rect, text_color: Color32, button_color: Color32) {
+        let mut ui = ui.new_child(egui::UiBuilder::new().max_rect(rect));
+        let entry = self.timers.primary_mut();
+        let best = entry.laps.best();
+        let worst = entry.laps.worst();
+
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Laps").color(text_color).size(12.0).strong());
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button(RichText::new("Export CSV").color(button_color)).clicked() {
+                    let dir = dirs::document_dir()
+                        .or_else(dirs::home_dir)
+                        .unwrap_or_default();
+                    entry.lap_status = Some(match entry.laps.export_csv(&dir) {
+                        Ok(path) => format!("Saved {}", path.display()),
+                        Err(err) => format!("Export failed: {err}"),
+                    });
+                }
+            });
+        });
+
+        if let Some(status) = &entry.lap_status {
+            ui.label(RichText::new(status).color(text_color).size(10.0));
+        }
+
+        egui::ScrollArea::vertical().auto_shrink([false, true]).show(&mut ui, |ui| {
+            egui::Grid::new("lap_table").num_columns(3).spacing([24.0, 2.0]).show(ui, |ui| {
+                for (i, lap) in entry.laps.as_slice().iter().enumerate().rev() {
+                    let color = if Some(i) == best {
+                        Color32::from_rgb(60, 140, 70) // Green
+                    } else if Some(i) == worst {
+                        Color32::from_rgb(180, 70, 60) // Red
+                    } else {
+                        text_color
+                    };
+                    ui.label(RichText::new(format!("#{}", i + 1)).color(color).size(13.0).monospace());
+                    ui.label(RichText::new(format_lap_time(lap.split)).color(color).size(13.0).monospace());
+                    ui.label(RichText::new(format_lap_time(lap.total)).color(color).size(13.0).monospace());
+                    ui.end_row();
+                }
+            });
+        });
     }
 }
 
@@ -34,21 +783,88 @@ impl eframe::App for TimerApp {
         if self.overlay_mode {
             [0.0, 0.0, 0.0, 0.0]
         } else {
-            [0.86, 0.86, 0.84, 1.0] // Light warm gray
+            self.config.theme.background.0.to_normalized_gamma_f32()
         }
     }
 
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Request continuous repaint for smooth animation
-        if self.timer.is_running() || self.overlay_mode {
+        if self.timers.any_running() || self.overlay_mode {
             ctx.request_repaint();
         }
 
-        // Update timer
-        self.timer.tick();
+        // Wake up now and then to notice config edits
+        if self.config_file.is_some() {
+            self.reload_config(ctx);
+            ctx.request_repaint_after(CONFIG_CHECK);
+        }
+
+        // Run commands from the control socket and D-Bus
+        while let Some(request) = self.requests.as_ref().and_then(|requests| requests.try_recv().ok()) {
+            let response = control::execute(&request.command, &mut self.timers);
+            if let Command::Launch(launch) = &request.command {
+                // Another launch in single-instance mode: bring this window forward instead
+                if launch.overlay && !self.overlay_mode {
+                    self.set_overlay(ctx, true);
+                }
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+                ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+            }
+            request.respond(response);
+        }
+
+        self.run_notification_actions(ctx);
+
+        // Update all timers
+        let events = self.timers.update_all();
+        if let Some(dbus) = &mut self.dbus {
+            let finished: Vec<String> = events
+                .iter()
+                .filter(|(_, event)| *event == TimerEvent::Finished)
+                .filter_map(|&(index, _)| self.timers.get(index))
+                .map(|entry| entry.name.clone())
+                .collect();
+            if let Err(err) = dbus.publish(&Status::of(self.timers.primary()), &finished) {
+                eprintln!("tiemrzzzz: D-Bus service stopped: {err}");
+                self.dbus = None;
+            }
+        }
+        for &(index, event) in &events {
+            let Some(entry) = self.timers.get(index) else {
+                continue;
+            };
+            match event {
+                TimerEvent::SegmentEnded => sound::beep(1320.0, 300),
+                TimerEvent::CountdownSecond => sound::beep(880.0, 120),
+                _ => {}
+            }
+            if event == TimerEvent::Finished {
+                self.alarm.ring(self.config.sounds.alarm(), self.config.sounds.repeat);
+                ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(egui::UserAttentionType::Critical));
+            }
+            if let Err(err) = self.config.hooks.run(event, entry) {
+                eprintln!("tiemrzzzz: {} hook failed: {err}", event.name());
+            }
+        }
+        self.post_notifications(&events);
+
+        let warning = Duration::from_secs(self.config.sounds.warning.0.into());
+        for entry in self.timers.iter_mut() {
+            if entry.take_warning(warning) {
+                alarm::tick();
+            }
+        }
+
+        // Typing only goes on while the countdown stays stopped
+        if !self.can_type_time() {
+            self.time_entry = None;
+        }
+        if !ctx.wants_keyboard_input() {
+            self.handle_keys(ctx);
+        }
 
         // Blink colon every 0.5 seconds when running
-        if self.timer.is_running() {
+        if self.timers.primary().timer.is_running() {
             self.colon_timer += ctx.input(|i| i.unstable_dt);
             if self.colon_timer >= 0.5 {
                 self.colon_timer = 0.0;
@@ -58,21 +874,22 @@ impl eframe::App for TimerApp {
             self.show_colon = true;
         }
 
-
-
         // Colors
-        let bg_color = if self.overlay_mode {
-            Color32::from_rgba_unmultiplied(30, 30, 30, 180) // Dark, semi-transparent
-        } else {
-            Color32::from_rgb(220, 220, 215) // Light warm gray
-        };
-        let digit_color = if self.overlay_mode {
-            Color32::from_rgba_unmultiplied(255, 255, 255, 240) // White, slightly transparent
-        } else {
-            Color32::from_rgb(80, 80, 75) // Dark gray
+        let theme = &self.config.theme;
+        let mut bg_color = if self.overlay_mode { theme.overlay_background.0 } else { theme.background.0 };
+        let mut digit_color = match (self.timers.primary().timer.is_overtime(), self.overlay_mode) {
+            (true, true) => theme.overlay_overtime.0,
+            (true, false) => theme.overtime.0,
+            (false, true) => theme.overlay_digits.0,
+            (false, false) => theme.digits.0,
         };
-        let button_color = Color32::from_rgb(100, 100, 95);
-        let button_hover = Color32::from_rgb(70, 70, 65);
+        // The overlay can't show finished messages, so it flashes until clicked
+        let ringing = self.timers.iter().any(|entry| entry.finished_notice.is_some());
+        if self.overlay_mode && ringing && ctx.input(|i| i.time).fract() < 0.5 {
+            (bg_color, digit_color) = (theme.overlay_overtime.0, theme.overlay_background.0.to_opaque());
+        }
+        let button_color = theme.buttons.0;
+        let button_hover = theme.button_hover.0;
 
         // Set the background with rounded corners for overlay
         let frame = if self.overlay_mode {
@@ -84,27 +901,128 @@ impl eframe::App for TimerApp {
             egui::Frame::default().fill(bg_color)
         };
 
+        // Timer list (full UI only)
+        if !self.overlay_mode {
+            egui::TopBottomPanel::top("timer_list")
+                .frame(egui::Frame::default().fill(bg_color).inner_margin(egui::Margin::same(6.0)))
+                .show_separator_line(false)
+                .show(ctx, |ui| {
+                    self.draw_timer_list(ui, digit_color, button_color);
+                    self.draw_preset_bar(ui, digit_color, button_color);
+                    if let Some(status) = &self.config_status {
+                        ui.label(RichText::new(status).size(11.0).color(self.config.theme.overtime.0));
+                    }
+                });
+        }
+
         egui::CentralPanel::default().frame(frame).show(ctx, |ui| {
             let available_size = ui.available_size();
-            
+            let panel_center = ui.max_rect().center();
+
+            let primary = self.timers.primary();
+            let show_laps = !self.overlay_mode && primary.timer.kind == TimerKind::Stopwatch && !primary.laps.is_empty();
+            let phase_label = primary.phase_label();
+            let next_up = primary.sequence.as_ref().and_then(Sequence::next_up);
+            let time = match &self.time_entry {
+                Some(entry) => entry.parts(),
+                None => TimeParts {
+                    negative: primary.timer.is_overtime(),
+                    ..TimeParts::from(primary.timer.shown())
+                },
+            };
+
             // In overlay mode, position time at center of small window
-            let center = if self.overlay_mode {
-                Pos2::new(available_size.x / 2.0, available_size.y / 2.0)
+            let center = if self.overlay_mode && phase_label.is_some() {
+                // Nudge down to fit the phase label above
+                panel_center + Vec2::new(0.0, 6.0)
+            } else if self.overlay_mode {
+                panel_center
+            } else if show_laps {
+                // Make room for the lap table below the digits
+                panel_center - Vec2::new(0.0, 100.0)
             } else {
-                Pos2::new(available_size.x / 2.0, available_size.y / 2.0 - 30.0)
+                panel_center - Vec2::new(0.0, 30.0)
             };
 
             // Draw the time display
-            draw_time(
+            // Leave a margin in full mode; the overlay already has its frame margin
+            let max_width = if self.overlay_mode {
+                ctx.screen_rect().width()
+            } else {
+                available_size.x - 20.0
+            };
+
+            // The typing cursor blinks twice a second
+            let now = ctx.input(|i| i.time);
+            let blink = Blink {
+                colon: self.show_colon,
+                cursor: self.time_entry.as_ref().map(|_| now % 1.0 < 0.5),
+            };
+            if blink.cursor.is_some() {
+                ctx.request_repaint_after(Duration::from_secs_f64(0.5 - now % 0.5));
+            }
+            let display = draw_time(
                 ui,
-                self.timer.minutes(),
-                self.timer.seconds(),
+                time,
                 center,
+                max_width,
                 digit_color,
-                self.show_colon,
-                self.overlay_mode,
+                blink,
+                if self.overlay_mode { self.config.overlay.pixels() } else { self.config.theme.pixels() },
             );
 
+            // Click the digits of a stopped countdown to type a time
+            if self.can_type_time() {
+                let mut response = ui
+                    .interact(display, egui::Id::new("time_display"), egui::Sense::click())
+                    .on_hover_cursor(egui::CursorIcon::Text);
+                if self.time_entry.is_none() {
+                    response = response.on_hover_text(format!("Click or press {} to type a time", self.config.shortcuts.edit_time));
+                }
+                if response.clicked() {
+                    self.time_entry.get_or_insert_with(TimeEntry::default);
+                }
+            }
+
+            if let Some(label) = phase_label {
+                let (offset, size) = if self.overlay_mode { (27.0, 10.0) } else { (50.0, 16.0) };
+                ui.painter().text(
+                    center - Vec2::new(0.0, offset),
+                    egui::Align2::CENTER_BOTTOM,
+                    label,
+                    FontId::proportional(size),
+                    digit_color,
+                );
+            }
+
+            if self.time_entry.is_some() {
+                ui.painter().text(
+                    center + Vec2::new(0.0, 45.0),
+                    egui::Align2::CENTER_TOP,
+                    "Type a time · Enter to set · Esc to cancel",
+                    FontId::proportional(13.0),
+                    button_color,
+                );
+            }
+
+            if let (Some(next), false) = (next_up, self.overlay_mode) {
+                ui.painter().text(
+                    center + Vec2::new(0.0, 45.0),
+                    egui::Align2::CENTER_TOP,
+                    next,
+                    FontId::proportional(13.0),
+                    button_color,
+                );
+            }
+
+            if show_laps {
+                let lap_rect = egui::Rect::from_min_size(
+                    Pos2::new(center.x - 120.0, center.y + 45.0),
+                    Vec2::new(240.0, 100.0),
+                );
+                self.draw_laps(ui, lap_rect, digit_color, button_color);
+            }
+
             if self.overlay_mode {
                 let rect = ui.max_rect();
                 
@@ -114,6 +1032,19 @@ impl eframe::App for TimerApp {
                 if bg_response.drag_started() {
                     ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
                 }
+
+                // Right-click to pick a preset
+                bg_response.context_menu(|ui| {
+                    if self.presets.as_slice().is_empty() {
+                        ui.label("No presets");
+                    }
+                    for preset in self.presets.as_slice() {
+                        if ui.button(preset.label()).clicked() {
+                            self.timers.primary_mut().load_preset(preset);
+                            ui.close_menu();
+                        }
+                    }
+                });
                 
                 // Exit button - Positioned manually at top-right
                 let exit_size = Vec2::new(24.0, 24.0);
@@ -131,14 +1062,13 @@ impl eframe::App for TimerApp {
                 
                 // Check exit click FIRST (takes priority)
                 if exit_response.on_hover_text("Exit focus mode").clicked() {
-                    self.overlay_mode = false;
-                    // Request window resize back to normal
-                    ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(Vec2::new(400.0, 400.0)));
-                    ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(true));
-                    ctx.send_viewport_cmd(egui::ViewportCommand::Transparent(false));
+                    self.set_overlay(ctx, false);
+                } else if bg_response.clicked() && ringing {
+                    // The overlay has no room for finished messages; a click dismisses them
+                    self.timers.iter_mut().for_each(NamedTimer::dismiss);
                 } else if bg_response.clicked() {
                     // Only toggle if exit wasn't clicked
-                    self.timer.toggle();
+                    self.timers.primary_mut().timer.toggle();
                 }
             } else {
                 // Full UI mode
@@ -147,54 +1077,177 @@ impl eframe::App for TimerApp {
                 ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
                     ui.add_space(40.0);
 
-                    // Time adjustment controls (only when stopped)
-                    if self.timer.state == TimerState::Stopped {
+                    // Pomodoro settings (only when stopped)
+                    if self.timers.primary().timer.state == TimerState::Stopped && self.timers.primary().mode() == Mode::Pomodoro {
+                        ui.add_space(10.0);
+                        ui.horizontal(|ui| {
+                            ui.add_space((available_size.x - 360.0) / 2.0);
+
+                            let config = self.pomodoro_config.clone();
+                            let settings: [(&str, u32, ConfigStep); 4] = [
+                                ("Work", config.work / 60, |c, d| c.work = step_minutes(c.work, d)),
+                                ("Short", config.short_break / 60, |c, d| c.short_break = step_minutes(c.short_break, d)),
+                                ("Long", config.long_break / 60, |c, d| c.long_break = step_minutes(c.long_break, d)),
+                                ("Every", config.long_break_every, |c, d| c.long_break_every = c.long_break_every.saturating_add_signed(d).clamp(1, 12)),
+                            ];
+
+                            for (i, (label, value, adjust)) in settings.into_iter().enumerate() {
+                                if i > 0 {
+                                    ui.add_space(8.0);
+                                }
+                                ui.vertical(|ui| {
+                                    ui.label(RichText::new(label).color(digit_color).size(12.0));
+                                    ui.horizontal(|ui| {
+                                        if ui.button(RichText::new("−").size(18.0).color(button_color)).clicked() {
+                                            self.adjust_pomodoro(|c| adjust(c, -1));
+                                        }
+                                        ui.label(RichText::new(format!("{value:02}")).color(digit_color).size(16.0));
+                                        if ui.button(RichText::new("+").size(18.0).color(button_color)).clicked() {
+                                            self.adjust_pomodoro(|c| adjust(c, 1));
+                                        }
+                                    });
+                                });
+                            }
+                        });
+                    }
+
+                    // Time adjustment controls (only when a stopped countdown)
+                    if self.timers.primary().timer.state == TimerState::Stopped && self.timers.primary().mode() == Mode::Countdown {
                         ui.add_space(10.0);
                         ui.horizontal(|ui| {
-                            ui.add_space((available_size.x - 300.0) / 2.0);
-                            
+                            ui.add_space((available_size.x - 330.0) / 2.0);
+
+                            // Hours controls
+                            ui.vertical(|ui| {
+                                ui.label(RichText::new("Hours").color(digit_color).size(12.0));
+                                ui.horizontal(|ui| {
+                                    if ui.button(RichText::new("−").size(18.0).color(button_color)).clicked() {
+                                        self.timers.primary_mut().timer.add_hours(-1);
+                                    }
+                                    ui.label(RichText::new(format!("{:02}", self.timers.primary().timer.hours())).color(digit_color).size(16.0));
+                                    if ui.button(RichText::new("+").size(18.0).color(button_color)).clicked() {
+                                        self.timers.primary_mut().timer.add_hours(1);
+                                    }
+                                });
+                            });
+
+                            ui.add_space(24.0);
+
                             // Minutes controls
                             ui.vertical(|ui| {
                                 ui.label(RichText::new("Minutes").color(digit_color).size(12.0));
                                 ui.horizontal(|ui| {
                                     if ui.button(RichText::new("−").size(18.0).color(button_color)).clicked() {
-                                        self.timer.add_minutes(-1);
+                                        self.timers.primary_mut().timer.add_minutes(-1);
                                     }
-                                    ui.label(RichText::new(format!("{:02}", self.timer.minutes())).color(digit_color).size(16.0));
+                                    ui.label(RichText::new(format!("{:02}", self.timers.primary().timer.minutes())).color(digit_color).size(16.0));
                                     if ui.button(RichText::new("+").size(18.0).color(button_color)).clicked() {
-                                        self.timer.add_minutes(1);
+                                        self.timers.primary_mut().timer.add_minutes(1);
                                     }
                                 });
                             });
 
-                            ui.add_space(40.0);
+                            ui.add_space(24.0);
 
                             // Seconds controls
                             ui.vertical(|ui| {
                                 ui.label(RichText::new("Seconds").color(digit_color).size(12.0));
                                 ui.horizontal(|ui| {
                                     if ui.button(RichText::new("−").size(18.0).color(button_color)).clicked() {
-                                        self.timer.add_seconds(-10);
+                                        self.timers.primary_mut().timer.add_seconds(-10);
                                     }
-                                    ui.label(RichText::new(format!("{:02}", self.timer.seconds())).color(digit_color).size(16.0));
+                                    ui.label(RichText::new(format!("{:02}", self.timers.primary().timer.seconds())).color(digit_color).size(16.0));
                                     if ui.button(RichText::new("+").size(18.0).color(button_color)).clicked() {
-                                        self.timer.add_seconds(10);
+                                        self.timers.primary_mut().timer.add_seconds(10);
                                     }
                                 });
                             });
                         });
                     }
 
+                    // Add time to a countdown that is underway without stopping it
+                    let entry = self.timers.primary();
+                    if entry.timer.state != TimerState::Stopped && entry.timer.kind == TimerKind::Countdown && entry.sequence.is_none() {
+                        let mut extend = None;
+                        let snooze = &self.config.defaults.snooze;
+                        ui.add_space(10.0);
+                        ui.horizontal(|ui| {
+                            ui.add_space((available_size.x - snooze.len() as f32 * 64.0) / 2.0);
+                            for &Seconds(seconds) in snooze {
+                                let button = egui::Button::new(RichText::new(extend_label(seconds)).size(14.0).color(button_color))
+                                    .fill(Color32::TRANSPARENT)
+                                    .min_size(Vec2::new(56.0, 24.0));
+                                if ui.add(button).on_hover_cursor(egui::CursorIcon::PointingHand).clicked() {
+                                    extend = Some(seconds);
+                                }
+                            }
+                        });
+                        if let Some(seconds) = extend {
+                            self.timers.primary_mut().extend(seconds as i32);
+                        }
+                    }
+
+                    // Mode switch (only when stopped)
+                    if self.timers.primary().timer.state == TimerState::Stopped {
+                        ui.add_space(10.0);
+                        ui.horizontal(|ui| {
+                            let mode = self.timers.primary().mode();
+                            let row_width = if mode == Mode::Stopwatch { 310.0 } else { 380.0 };
+                            ui.add_space((available_size.x - row_width) / 2.0);
+
+                            let toggle_button = |label: &str, selected: bool| {
+                                let text = RichText::new(label).size(13.0).color(if selected { Color32::WHITE } else { button_color });
+                                egui::Button::new(text)
+                                    .fill(if selected { button_color } else { Color32::TRANSPARENT })
+                                    .min_size(Vec2::new(0.0, 24.0))
+                            };
+
+                            for option in Mode::ALL {
+                                let selected = mode == option;
+                                if ui.add(toggle_button(option.label(), selected)).on_hover_cursor(egui::CursorIcon::PointingHand).clicked() && !selected {
+                                    self.set_mode(option);
+                                }
+                            }
+
+                            match mode {
+                                Mode::Countdown => {
+                                    let enabled = self.timers.primary().timer.overtime;
+                                    if ui.add(toggle_button("⏩ Overtime", enabled)).on_hover_cursor(egui::CursorIcon::PointingHand).on_hover_text("Keep counting past zero").clicked() {
+                                        self.timers.primary_mut().timer.overtime = !enabled;
+                                    }
+                                }
+                                Mode::Pomodoro => {
+                                    let enabled = self.pomodoro_config.auto_advance;
+                                    if ui.add(toggle_button("🔁 Auto", enabled)).on_hover_cursor(egui::CursorIcon::PointingHand).on_hover_text("Start the next phase automatically").clicked() {
+                                        self.adjust_pomodoro(|c| c.auto_advance = !enabled);
+                                    }
+                                }
+                                Mode::Intervals => {
+                                    let open = self.show_sequence_settings;
+                                    if ui.add(toggle_button("⚙ Setup", open)).on_hover_cursor(egui::CursorIcon::PointingHand).on_hover_text("Edit segments and rounds").clicked() {
+                                        self.show_sequence_settings = !open;
+                                    }
+                                }
+                                Mode::Stopwatch => {}
+                            }
+                        });
+                    }
+
                     ui.add_space(15.0);
 
                     // Main control buttons
+                    let mode = self.timers.primary().mode();
                     ui.horizontal(|ui| {
-                        ui.add_space((available_size.x - 280.0) / 2.0);
+                        let row_width = match mode {
+                            Mode::Countdown | Mode::Intervals => 280.0,
+                            Mode::Stopwatch | Mode::Pomodoro => 370.0,
+                        };
+                        ui.add_space((available_size.x - row_width) / 2.0);
 
                         let button_size = Vec2::new(80.0, 35.0);
                         
                         // Start/Pause button
-                        let start_text = match self.timer.state {
+                        let start_text = match self.timers.primary().timer.state {
                             TimerState::Running => "Pause",
                             TimerState::Paused => "Resume",
                             TimerState::Stopped => "Start",
@@ -207,11 +1260,42 @@ impl eframe::App for TimerApp {
                         .min_size(button_size);
                         
                         if ui.add(start_btn).on_hover_cursor(egui::CursorIcon::PointingHand).clicked() {
-                            self.timer.toggle();
+                            self.timers.primary_mut().timer.toggle();
                         }
 
                         ui.add_space(10.0);
 
+                        // Lap button (stopwatch only)
+                        if mode == Mode::Stopwatch {
+                            let lap_btn = egui::Button::new(
+                                RichText::new("Lap").size(16.0).color(Color32::WHITE)
+                            )
+                            .fill(button_color)
+                            .min_size(button_size);
+
+                            let lap_enabled = self.timers.primary().timer.is_running();
+                            if ui.add_enabled(lap_enabled, lap_btn).on_hover_cursor(egui::CursorIcon::PointingHand).on_hover_text(format!("Record a lap ({})", self.config.shortcuts.lap)).clicked() {
+                                self.timers.primary_mut().record_lap();
+                            }
+
+                            ui.add_space(10.0);
+                        }
+
+                        // Skip button (Pomodoro only)
+                        if mode == Mode::Pomodoro {
+                            let skip_btn = egui::Button::new(
+                                RichText::new("Skip").size(16.0).color(Color32::WHITE)
+                            )
+                            .fill(button_color)
+                            .min_size(button_size);
+
+                            if ui.add(skip_btn).on_hover_cursor(egui::CursorIcon::PointingHand).on_hover_text("Go to the next phase").clicked() {
+                                self.timers.primary_mut().skip_phase();
+                            }
+
+                            ui.add_space(10.0);
+                        }
+
                         // Reset button
                         let reset_btn = egui::Button::new(
                             RichText::new("Reset").size(16.0).color(Color32::WHITE)
@@ -220,7 +1304,7 @@ impl eframe::App for TimerApp {
                         .min_size(button_size);
                         
                         if ui.add(reset_btn).on_hover_cursor(egui::CursorIcon::PointingHand).clicked() {
-                            self.timer.reset();
+                            self.timers.primary_mut().reset();
                         }
 
                         ui.add_space(10.0);
@@ -233,30 +1317,103 @@ impl eframe::App for TimerApp {
                         .min_size(button_size);
                         
                         if ui.add(overlay_btn).on_hover_cursor(egui::CursorIcon::PointingHand).on_hover_text("Always on top - click timer to pause/resume").clicked() {
-                            self.overlay_mode = true;
-                            // Make window small, always on top, no decorations, transparent
-                            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(Vec2::new(160.0, 70.0)));
-                            ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(egui::WindowLevel::AlwaysOnTop));
-                            ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(false));
-                            ctx.send_viewport_cmd(egui::ViewportCommand::Transparent(true));
+                            self.set_overlay(ctx, true);
                         }
                     });
                 });
 
-                // Show "Time's up!" message when finished
-                if self.timer.is_finished() {
-                    egui::Window::new("Timer Complete")
+                if self.show_sequence_settings {
+                    self.draw_sequence_settings(ctx);
+                }
+
+                self.draw_rename(ctx);
+                self.draw_preset_editor(ctx);
+                self.draw_help(ctx);
+
+                // Show each timer's finished message once its countdown reaches zero
+                let show_names = self.timers.len() > 1;
+                let snooze = &self.config.defaults.snooze;
+                for (i, entry) in self.timers.iter_mut().enumerate() {
+                    let Some(notice) = entry.finished_notice.clone() else {
+                        continue;
+                    };
+                    let title = if show_names { format!("{} complete", entry.name) } else { "Timer Complete".to_owned() };
+
+                    egui::Window::new(title)
+                        .id(egui::Id::new(("finished", i)))
                         .collapsible(false)
                         .resizable(false)
-                        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                        .anchor(egui::Align2::CENTER_CENTER, [0.0, i as f32 * 24.0])
                         .show(ctx, |ui| {
-                            ui.label(RichText::new("⏰ Time's up!").size(24.0).color(digit_color));
-                            if ui.button("OK").clicked() {
-                                self.timer.reset();
-                            }
+                            ui.label(RichText::new(notice).size(24.0).color(digit_color));
+                            ui.horizontal(|ui| {
+                                if ui.button("OK").clicked() {
+                                    entry.dismiss();
+                                }
+
+                                if entry.can_snooze() {
+                                    for &Seconds(seconds) in snooze {
+                                        if ui.button(extend_label(seconds)).on_hover_text("Snooze").clicked() {
+                                            entry.snooze(seconds);
+                                        }
+                                    }
+                                    if ui.button("Restart").on_hover_text("Run the same countdown again").clicked() {
+                                        entry.restart();
+                                    }
+                                }
+
+                                // Manual Pomodoro advance waits for the next phase to be started
+                                if let Some(pomodoro) = &entry.pomodoro {
+                                    if entry.timer.state == TimerState::Stopped
+                                        && ui.button(format!("Start {}", pomodoro.phase().label())).clicked()
+                                    {
+                                        entry.finished_notice = None;
+                                        entry.timer.start();
+                                    }
+                                }
+                            });
                         });
                 }
             }
         });
+
+        if !self.timers.iter().any(|entry| entry.finished_notice.is_some()) {
+            self.alarm.stop();
+        }
+
+        self.track_window(ctx);
+        if let Some(wait) = self.save_session() {
+            ctx.request_repaint_after(wait);
+        }
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // Don't let the throttle drop the last change
+        self.next_session_save = Instant::now();
+        self.save_session();
+    }
+}
+
+/// Adjusts one Pomodoro setting by a signed step
+type ConfigStep = fn(&mut PomodoroConfig, i32);
+
+/// Step a duration in seconds by whole minutes, keeping at least one minute
+fn step_minutes(seconds: u32, minutes: i32) -> u32 {
+    (seconds / 60).saturating_add_signed(minutes).clamp(1, 180) * 60
+}
+
+/// Button text for adding time, e.g. "+5 min" or "+30 s"
+fn extend_label(seconds: u32) -> String {
+    format!("+{}", Seconds(seconds).label())
+}
+
+/// Compact time for the timer list, e.g. "04:30" or "-1:02:03"
+fn short_time(timer: &Timer) -> String {
+    let time = TimeParts::from(timer.shown());
+    let sign = if timer.is_overtime() { "-" } else { "" };
+    if time.hours > 0 {
+        format!("{sign}{}:{:02}:{:02}", time.hours, time.minutes, time.seconds)
+    } else {
+        format!("{sign}{:02}:{:02}", time.minutes, time.seconds)
     }
 }
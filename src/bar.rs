@@ -0,0 +1,202 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::control;
+
+/// How often the running timer is asked for its status
+const POLL: Duration = Duration::from_millis(250);
+
+/// Output style of `tiemrzzzz bar`
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum BarFormat {
+    /// One JSON object per line with the timer's state
+    #[default]
+    Json,
+    /// JSON for waybar custom modules with `"return-type": "json"`
+    Waybar,
+    /// Plain text for polybar, i3blocks and the like
+    Text,
+}
+
+/// The parts of a status reply that a bar shows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BarStatus {
+    pub name: String,
+    pub state: String,
+    pub remaining: f64,
+    pub overtime: bool,
+    /// Percent of the countdown used
+    pub progress: u8,
+    pub display: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase: Option<String>,
+}
+
+impl BarStatus {
+    /// Short label, e.g. "⏸ 04:30 · Work 1/4"
+    fn text(&self) -> String {
+        let mut text = self.display.clone();
+        if self.state == "paused" {
+            text.insert_str(0, "⏸ ");
+        }
+        if let Some(phase) = &self.phase {
+            text.push_str(" · ");
+            text.push_str(phase);
+        }
+        text
+    }
+}
+
+#[derive(Deserialize)]
+struct Reply {
+    status: Option<BarStatus>,
+}
+
+/// Parse a control socket reply into the bar's view of it
+fn parse_reply(reply: &str) -> Option<BarStatus> {
+    serde_json::from_str::<Reply>(reply).ok()?.status
+}
+
+/// Ask the running timer for its status; `None` when no timer is open
+fn poll() -> io::Result<Option<BarStatus>> {
+    match control::send(&control::socket_path(), "status") {
+        Ok(reply) => Ok(parse_reply(&reply)),
+        Err(err) if err.kind() == io::ErrorKind::Unsupported => Err(err),
+        Err(_) => Ok(None),
+    }
+}
+
+/// One line of bar output; a missing status means no timer is open
+pub fn format_line(status: Option<&BarStatus>, format: BarFormat) -> String {
+    match (format, status) {
+        (BarFormat::Json, Some(status)) => serde_json::to_string(status).unwrap_or_default(),
+        (BarFormat::Json, None) => json!({ "state": "offline" }).to_string(),
+        (BarFormat::Waybar, Some(status)) => {
+            let mut tooltip = format!("{} · {}", status.name, status.state);
+            if let Some(phase) = &status.phase {
+                tooltip = format!("{tooltip} · {phase}");
+            }
+            let class = if status.overtime { "overtime" } else { status.state.as_str() };
+            json!({
+                "text": status.text(),
+                "tooltip": tooltip,
+                "alt": status.state,
+                "class": class,
+                "percentage": status.progress,
+            })
+            .to_string()
+        }
+        // Empty text hides the module while no timer is open
+        (BarFormat::Waybar, None) => json!({ "text": "", "alt": "offline", "class": "offline" }).to_string(),
+        (BarFormat::Text, Some(status)) => status.text(),
+        (BarFormat::Text, None) => String::new(),
+    }
+}
+
+/// Keep writing the running timer's status until the output goes away
+///
+/// Lines go to stdout, or replace the contents of `output` so bars that read
+/// a file always see a whole line. Only changes are written.
+pub fn run(format: BarFormat, output: Option<&Path>) -> io::Result<()> {
+    let mut last = None;
+    loop {
+        let line = format_line(poll()?.as_ref(), format);
+        if last.as_ref() != Some(&line) {
+            match output {
+                Some(path) => write_file(path, &line)?,
+                None => {
+                    let mut stdout = io::stdout().lock();
+                    writeln!(stdout, "{line}")?;
+                    stdout.flush()?;
+                }
+            }
+            last = Some(line);
+        }
+        thread::sleep(POLL);
+    }
+}
+
+fn write_file(path: &Path, line: &str) -> io::Result<()> {
+    let partial = path.with_extension("partial");
+    fs::write(&partial, format!("{line}\n"))?;
+    fs::rename(partial, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{execute, Command};
+    use crate::timers::TimerList;
+
+    fn status(state: &str, phase: Option<&str>) -> BarStatus {
+        BarStatus {
+            name: "Focus".to_owned(),
+            state: state.to_owned(),
+            remaining: 270.0,
+            overtime: false,
+            progress: 40,
+            display: "04:30".to_owned(),
+            phase: phase.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn reads_control_replies() {
+        let mut timers = TimerList::default();
+        timers.primary_mut().set_countdown(90);
+        let reply = execute(&Command::Status, &mut timers).to_json();
+        let status = parse_reply(&reply).unwrap();
+        assert_eq!((status.state.as_str(), status.display.as_str(), status.progress), ("stopped", "01:30", 0));
+
+        assert_eq!(parse_reply(r#"{"ok":false,"error":"nope"}"#), None);
+    }
+
+    #[test]
+    fn text_lines() {
+        assert_eq!(format_line(Some(&status("running", None)), BarFormat::Text), "04:30");
+        assert_eq!(
+            format_line(Some(&status("paused", Some("Work 1/4"))), BarFormat::Text),
+            "⏸ 04:30 · Work 1/4"
+        );
+        assert_eq!(format_line(None, BarFormat::Text), "");
+    }
+
+    #[test]
+    fn waybar_lines() {
+        let line = format_line(Some(&status("running", Some("Work 1/4"))), BarFormat::Waybar);
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["text"], "04:30 · Work 1/4");
+        assert_eq!(value["tooltip"], "Focus · running · Work 1/4");
+        assert_eq!(value["class"], "running");
+        assert_eq!(value["percentage"], 40);
+
+        let offline: serde_json::Value = serde_json::from_str(&format_line(None, BarFormat::Waybar)).unwrap();
+        assert_eq!((offline["text"].as_str(), offline["class"].as_str()), (Some(""), Some("offline")));
+    }
+
+    #[test]
+    fn json_lines() {
+        let line = format_line(Some(&status("running", None)), BarFormat::Json);
+        assert_eq!(
+            line,
+            r#"{"name":"Focus","state":"running","remaining":270.0,"overtime":false,"progress":40,"display":"04:30"}"#
+        );
+        assert_eq!(format_line(None, BarFormat::Json), r#"{"state":"offline"}"#);
+    }
+
+    #[test]
+    fn file_output_replaces_contents() {
+        let path = std::env::temp_dir().join(format!("tiemrzzzz-bar-{}.txt", std::process::id()));
+        write_file(&path, "04:30").unwrap();
+        write_file(&path, "04:29").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "04:29\n");
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,260 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+use crate::alarm::Tune;
+use crate::bar::BarFormat;
+
+use crate::timer::MAX_DURATION;
+
+/// Command-line options used to set up the first timer and the window
+#[derive(Debug, Default, Parser)]
+#[command(version, about = "A minimalist, pixel-style timer")]
+pub struct Args {
+    /// Countdown length, e.g. 25m, 1h20m, 90s, 12:30 or 1:02:03 (bare numbers are seconds)
+    #[arg(value_parser = parse_duration)]
+    pub duration: Option<u32>,
+
+    /// Start as a stopwatch instead of a countdown
+    #[arg(long, conflicts_with_all = ["duration", "preset"])]
+    pub stopwatch: bool,
+
+    /// Open in the small always-on-top focus overlay
+    #[arg(long)]
+    pub overlay: bool,
+
+    /// Start the timer right away
+    #[arg(long)]
+    pub start: bool,
+
+    /// Load a saved preset by name
+    #[arg(long, value_name = "NAME", conflicts_with = "duration")]
+    pub preset: Option<String>,
+
+    /// Window title
+    #[arg(long)]
+    pub title: Option<String>,
+
+    /// Window position on screen in pixels
+    #[arg(long, value_name = "X,Y", value_parser = parse_position)]
+    pub position: Option<[f32; 2]>,
+
+    /// Run in the terminal instead of opening a window
+    #[arg(long, conflicts_with_all = ["overlay", "position"])]
+    pub tui: bool,
+
+    /// Hand these options to an already running instance instead of opening another window
+    #[arg(long, conflicts_with = "tui")]
+    pub single_instance: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Action>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Action {
+    /// Control the running timer: start, pause, toggle, reset, set DURATION, add [-]DURATION or status
+    Ctl {
+        #[arg(required = true, num_args = 1.., allow_hyphen_values = true, trailing_var_arg = true)]
+        command: Vec<String>,
+    },
+    /// Keep printing the running timer's status for status bars like waybar or polybar
+    Bar {
+        /// Output style
+        #[arg(long, value_enum, default_value_t)]
+        format: BarFormat,
+
+        /// Write each update to this file instead of stdout
+        #[arg(long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Play an alarm tune, or save it as a WAV file
+    Alarm {
+        #[arg(value_enum, default_value_t)]
+        tune: Tune,
+
+        /// Volume from 0.0 to 1.0
+        #[arg(long, default_value_t = 0.3, value_parser = parse_volume)]
+        volume: f32,
+
+        /// Write the tune to this WAV file instead of playing it
+        #[arg(long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Parse a human-friendly duration into seconds
+///
+/// Accepts unit groups (`1h20m`, `90s`, `2h 5m 3s`), clock notation
+/// (`12:30` is minutes and seconds, `1:02:03` adds hours) and bare seconds.
+pub fn parse_duration(text: &str) -> Result<u32, String> {
+    let text = text.trim().to_ascii_lowercase();
+    if text.is_empty() {
+        return Err("empty duration".to_owned());
+    }
+
+    let seconds = if text.contains(':') {
+        parse_clock(&text)?
+    } else if text.chars().all(|c| c.is_ascii_digit()) {
+        parse_number(&text)?
+    } else {
+        parse_units(&text)?
+    };
+
+    if seconds > u64::from(MAX_DURATION) {
+        return Err("duration is longer than 99:59:59".to_owned());
+    }
+    Ok(seconds as u32)
+}
+
+fn parse_number(text: &str) -> Result<u64, String> {
+    text.parse().map_err(|_| format!("invalid number '{text}'"))
+}
+
+/// `MM:SS` or `H:MM:SS`
+fn parse_clock(text: &str) -> Result<u64, String> {
+    let parts = text.split(':').map(parse_number).collect::<Result<Vec<_>, _>>()?;
+    let (hours, minutes, seconds) = match parts[..] {
+        [minutes, seconds] => (0, minutes, seconds),
+        [hours, minutes, seconds] if minutes < 60 => (hours, minutes, seconds),
+        _ => return Err(format!("invalid time '{text}', expected MM:SS or H:MM:SS")),
+    };
+    if seconds >= 60 {
+        return Err(format!("invalid time '{text}', seconds must be below 60"));
+    }
+    Ok(hours * 3600 + minutes * 60 + seconds)
+}
+
+/// Number and unit groups, e.g. `1h20m` or `2h 5m 3s`
+fn parse_units(text: &str) -> Result<u64, String> {
+    let mut total = 0u64;
+    let mut number = String::new();
+    for c in text.chars().filter(|c| !c.is_whitespace()) {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let scale = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(format!("unknown unit '{c}' in '{text}', use h, m or s")),
+        };
+        if number.is_empty() {
+            return Err(format!("missing number before '{c}' in '{text}'"));
+        }
+        total = total.saturating_add(parse_number(&number)?.saturating_mul(scale));
+        number.clear();
+    }
+    if !number.is_empty() {
+        return Err(format!("missing unit after '{number}' in '{text}'"));
+    }
+    Ok(total)
+}
+
+/// Parse an `X,Y` screen position
+pub fn parse_position(text: &str) -> Result<[f32; 2], String> {
+    let (x, y) = text.split_once(',').ok_or_else(|| format!("invalid position '{text}', expected X,Y"))?;
+    let coord = |value: &str| value.trim().parse::<f32>().map_err(|_| format!("invalid coordinate '{value}'"));
+    Ok([coord(x)?, coord(y)?])
+}
+
+/// Parse a volume from 0.0 to 1.0
+fn parse_volume(text: &str) -> Result<f32, String> {
+    match text.trim().parse::<f32>() {
+        Ok(volume) if (0.0..=1.0).contains(&volume) => Ok(volume),
+        _ => Err(format!("invalid volume '{text}', expected 0.0 to 1.0")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_units() {
+        assert_eq!(parse_duration("25m"), Ok(1500));
+        assert_eq!(parse_duration("1h20m"), Ok(4800));
+        assert_eq!(parse_duration("90s"), Ok(90));
+        assert_eq!(parse_duration("2h 5m 3s"), Ok(7503));
+        assert_eq!(parse_duration("1M30S"), Ok(90));
+    }
+
+    #[test]
+    fn parses_clock_notation() {
+        assert_eq!(parse_duration("12:30"), Ok(750));
+        assert_eq!(parse_duration("1:02:03"), Ok(3723));
+        assert_eq!(parse_duration("90:00"), Ok(5400));
+    }
+
+    #[test]
+    fn bare_numbers_are_seconds() {
+        assert_eq!(parse_duration("45"), Ok(45));
+    }
+
+    #[test]
+    fn rejects_invalid_durations() {
+        for text in ["", "m", "5x", "1h20", "12:60", "1:60:00", "1:2:3:4", "a:b"] {
+            assert!(parse_duration(text).is_err(), "{text} should be rejected");
+        }
+    }
+
+    #[test]
+    fn rejects_durations_over_the_limit() {
+        assert_eq!(parse_duration("99:59:59"), Ok(MAX_DURATION));
+        assert!(parse_duration("100h").is_err());
+    }
+
+    #[test]
+    fn parses_positions() {
+        assert_eq!(parse_position("100,200"), Ok([100.0, 200.0]));
+        assert_eq!(parse_position(" -20 , 5.5"), Ok([-20.0, 5.5]));
+        assert!(parse_position("100").is_err());
+        assert!(parse_position("a,b").is_err());
+    }
+
+    #[test]
+    fn parses_command_line() {
+        let args = Args::try_parse_from(["tiemrzzzz", "25m", "--start", "--overlay", "--title", "Tea", "--position", "10,20"]).unwrap();
+        assert_eq!(args.duration, Some(1500));
+        assert!(args.start && args.overlay && !args.stopwatch);
+        assert_eq!(args.title.as_deref(), Some("Tea"));
+        assert_eq!(args.position, Some([10.0, 20.0]));
+
+        assert!(Args::try_parse_from(["tiemrzzzz", "25m", "--stopwatch"]).is_err());
+        assert!(Args::try_parse_from(["tiemrzzzz", "25m", "--preset", "Tea"]).is_err());
+    }
+
+    #[test]
+    fn parses_ctl_subcommand() {
+        let args = Args::try_parse_from(["tiemrzzzz", "ctl", "add", "-1m"]).unwrap();
+        match args.command {
+            Some(Action::Ctl { command }) => assert_eq!(command, ["add", "-1m"]),
+            _ => panic!("expected ctl"),
+        }
+        assert!(Args::try_parse_from(["tiemrzzzz", "ctl"]).is_err());
+    }
+
+    #[test]
+    fn parses_bar_subcommand() {
+        let args = Args::try_parse_from(["tiemrzzzz", "bar", "--format", "waybar"]).unwrap();
+        match args.command {
+            Some(Action::Bar { format, output }) => assert_eq!((format, output), (BarFormat::Waybar, None)),
+            _ => panic!("expected bar"),
+        }
+        assert!(Args::try_parse_from(["tiemrzzzz", "bar", "--format", "xml"]).is_err());
+    }
+
+    #[test]
+    fn parses_alarm_subcommand() {
+        let args = Args::try_parse_from(["tiemrzzzz", "alarm", "fanfare", "--volume", "0.8", "--output", "a.wav"]).unwrap();
+        match args.command {
+            Some(Action::Alarm { tune, volume, output }) => {
+                assert_eq!((tune, volume, output), (Tune::Fanfare, 0.8, Some(PathBuf::from("a.wav"))))
+            }
+            _ => panic!("expected alarm"),
+        }
+        assert!(matches!(Args::try_parse_from(["tiemrzzzz", "alarm"]).unwrap().command, Some(Action::Alarm { tune: Tune::Chime, .. })));
+        assert!(Args::try_parse_from(["tiemrzzzz", "alarm", "--volume", "2"]).is_err());
+    }
+}
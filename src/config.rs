@@ -0,0 +1,484 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use eframe::egui::Color32;
+use serde::Deserialize;
+
+use crate::alarm::{AlarmSound, Tune};
+use crate::cli::parse_duration;
+use crate::display::{PixelStyle, FULL_PIXELS, OVERLAY_PIXELS};
+use crate::hooks::Hooks;
+use crate::keymap::Keymap;
+use crate::pomodoro::PomodoroConfig;
+use crate::timers::TimerDefaults;
+
+/// Settings from `config.toml`; every section and key is optional
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub defaults: Defaults,
+    pub theme: Theme,
+    pub overlay: Overlay,
+    pub sounds: Sounds,
+    pub notifications: Notifications,
+    pub shortcuts: Keymap,
+    pub hooks: Hooks,
+}
+
+/// How new timers and pomodoros start out
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Defaults {
+    /// Countdown length of new timers
+    pub duration: Seconds,
+    /// Keep counting past zero
+    pub overtime: bool,
+    /// Time added by the snooze and extend buttons
+    pub snooze: Vec<Seconds>,
+    pub pomodoro: PomodoroDefaults,
+}
+
+impl Default for Defaults {
+    fn default() -> Self {
+        let timer = TimerDefaults::default();
+        Self {
+            duration: Seconds(timer.duration),
+            overtime: timer.overtime,
+            snooze: vec![Seconds(60), Seconds(5 * 60), Seconds(10 * 60)],
+            pomodoro: PomodoroDefaults::default(),
+        }
+    }
+}
+
+impl Defaults {
+    pub fn timer(&self) -> TimerDefaults {
+        TimerDefaults {
+            duration: self.duration.0,
+            overtime: self.overtime,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PomodoroDefaults {
+    pub work: Seconds,
+    pub short_break: Seconds,
+    pub long_break: Seconds,
+    pub long_break_every: u32,
+    pub auto_advance: bool,
+}
+
+impl Default for PomodoroDefaults {
+    fn default() -> Self {
+        let config = PomodoroConfig::default();
+        Self {
+            work: Seconds(config.work),
+            short_break: Seconds(config.short_break),
+            long_break: Seconds(config.long_break),
+            long_break_every: config.long_break_every,
+            auto_advance: config.auto_advance,
+        }
+    }
+}
+
+impl PomodoroDefaults {
+    pub fn config(&self) -> PomodoroConfig {
+        PomodoroConfig {
+            work: self.work.0,
+            short_break: self.short_break.0,
+            long_break: self.long_break.0,
+            long_break_every: self.long_break_every,
+            auto_advance: self.auto_advance,
+        }
+    }
+}
+
+/// Colors and pixel size of the full window, plus the overlay's colors
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    pub background: Color,
+    pub digits: Color,
+    /// Digits of a countdown running past zero
+    pub overtime: Color,
+    pub buttons: Color,
+    pub button_hover: Color,
+    pub overlay_background: Color,
+    pub overlay_digits: Color,
+    pub overlay_overtime: Color,
+    pub pixel_size: f32,
+    pub pixel_gap: f32,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            background: Color(Color32::from_rgb(220, 220, 215)), // Light warm gray
+            digits: Color(Color32::from_rgb(80, 80, 75)),         // Dark gray
+            overtime: Color(Color32::from_rgb(190, 60, 50)),      // Dark red
+            buttons: Color(Color32::from_rgb(100, 100, 95)),
+            button_hover: Color(Color32::from_rgb(70, 70, 65)),
+            overlay_background: Color(Color32::from_rgba_unmultiplied(30, 30, 30, 180)), // Dark, semi-transparent
+            overlay_digits: Color(Color32::from_rgba_unmultiplied(255, 255, 255, 240)), // White, slightly transparent
+            overlay_overtime: Color(Color32::from_rgba_unmultiplied(255, 110, 90, 240)), // Warm red, slightly transparent
+            pixel_size: FULL_PIXELS.size,
+            pixel_gap: FULL_PIXELS.gap,
+        }
+    }
+}
+
+impl Theme {
+    pub fn pixels(&self) -> PixelStyle {
+        PixelStyle {
+            size: self.pixel_size,
+            gap: self.pixel_gap,
+        }
+    }
+}
+
+/// Size of the focus overlay window and its digits
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Overlay {
+    pub width: f32,
+    pub height: f32,
+    pub pixel_size: f32,
+    pub pixel_gap: f32,
+}
+
+impl Default for Overlay {
+    fn default() -> Self {
+        Self {
+            width: 160.0,
+            height: 70.0,
+            pixel_size: OVERLAY_PIXELS.size,
+            pixel_gap: OVERLAY_PIXELS.gap,
+        }
+    }
+}
+
+impl Overlay {
+    pub fn size(&self) -> [f32; 2] {
+        [self.width, self.height]
+    }
+
+    pub fn pixels(&self) -> PixelStyle {
+        PixelStyle {
+            size: self.pixel_size,
+            gap: self.pixel_gap,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Sounds {
+    pub enabled: bool,
+    /// Volume of beeps and alarms from 0.0 to 1.0
+    pub volume: f32,
+    /// Alarm played when a timer finishes
+    pub tune: Tune,
+    /// WAV or OGG file to play instead of the tune
+    pub finished: Option<PathBuf>,
+    /// Keep playing the alarm until the finished message is dismissed
+    pub repeat: bool,
+    /// Tick this long before a countdown ends; 0 turns it off
+    pub warning: Seconds,
+}
+
+impl Sounds {
+    pub fn alarm(&self) -> AlarmSound {
+        match &self.finished {
+            Some(path) => AlarmSound::File(path.clone()),
+            None => AlarmSound::Tune(self.tune),
+        }
+    }
+}
+
+impl Default for Sounds {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            volume: 0.3,
+            tune: Tune::default(),
+            finished: None,
+            repeat: true,
+            warning: Seconds(0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Notifications {
+    /// Post a desktop notification when a timer finishes or changes phase
+    pub enabled: bool,
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// A length written as on the command line ("25m", "1:30") or as seconds
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "RawSeconds")]
+pub struct Seconds(pub u32);
+
+impl Seconds {
+    /// Length for a button, e.g. "5 min" or "30 s"
+    pub fn label(self) -> String {
+        match self.0 {
+            s if s % 3600 == 0 => format!("{} h", s / 3600),
+            s if s % 60 == 0 => format!("{} min", s / 60),
+            s => format!("{s} s"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawSeconds {
+    Number(u32),
+    Text(String),
+}
+
+impl TryFrom<RawSeconds> for Seconds {
+    type Error = String;
+
+    fn try_from(raw: RawSeconds) -> Result<Self, String> {
+        match raw {
+            RawSeconds::Number(seconds) => parse_duration(&seconds.to_string()),
+            RawSeconds::Text(text) => parse_duration(&text),
+        }
+        .map(Seconds)
+    }
+}
+
+/// A `#RRGGBB` or `#RRGGBBAA` color
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Color(pub Color32);
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        let invalid = || format!("invalid color \"{text}\", expected #RRGGBB or #RRGGBBAA");
+        let hex = text.trim().strip_prefix('#').ok_or_else(invalid)?;
+        if !(hex.len() == 6 || hex.len() == 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2).unwrap_or("ff"), 16).map_err(|_| invalid());
+        Ok(Color(Color32::from_rgba_unmultiplied(channel(0)?, channel(2)?, channel(4)?, channel(6)?)))
+    }
+}
+
+impl Config {
+    /// Default location, e.g. `~/.config/tiemrzzzz/config.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("tiemrzzzz").join("config.toml"))
+    }
+
+    /// Read the config from `path`; a missing file gives the defaults
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let config: Self = toml::from_str(text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        config.validate().map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        Ok(config)
+    }
+
+    /// Check the values the types alone don't rule out
+    fn validate(&self) -> Result<(), String> {
+        let check = |name: &str, value: f32, min: f32, max: f32| {
+            if (min..=max).contains(&value) {
+                Ok(())
+            } else {
+                Err(format!("{name} must be between {min} and {max}, got {value}"))
+            }
+        };
+        check("theme.pixel_size", self.theme.pixel_size, 1.0, 40.0)?;
+        check("theme.pixel_gap", self.theme.pixel_gap, 0.0, 20.0)?;
+        check("overlay.width", self.overlay.width, 80.0, 2000.0)?;
+        check("overlay.height", self.overlay.height, 40.0, 2000.0)?;
+        check("overlay.pixel_size", self.overlay.pixel_size, 1.0, 40.0)?;
+        check("overlay.pixel_gap", self.overlay.pixel_gap, 0.0, 20.0)?;
+        check("sounds.volume", self.sounds.volume, 0.0, 1.0)?;
+        if self.defaults.snooze.len() > 6 || self.defaults.snooze.contains(&Seconds(0)) {
+            return Err("defaults.snooze must list at most 6 lengths, none of them 0".to_owned());
+        }
+        let pomodoro = &self.defaults.pomodoro;
+        for (name, length) in [("work", pomodoro.work), ("short_break", pomodoro.short_break), ("long_break", pomodoro.long_break)] {
+            if length == Seconds(0) {
+                return Err(format!("defaults.pomodoro.{name} must be longer than 0"));
+            }
+        }
+        if pomodoro.long_break_every == 0 {
+            return Err("defaults.pomodoro.long_break_every must be at least 1".to_owned());
+        }
+        self.shortcuts.validate()
+    }
+}
+
+/// The config file, remembered so it can be read again when it changes
+pub struct ConfigFile {
+    path: PathBuf,
+    /// Modification time at the last read; `None` if the file didn't exist
+    modified: Option<SystemTime>,
+}
+
+impl ConfigFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, modified: None }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn modified(&self) -> Option<SystemTime> {
+        fs::metadata(&self.path).and_then(|meta| meta.modified()).ok()
+    }
+
+    pub fn load(&mut self) -> io::Result<Config> {
+        self.modified = self.modified();
+        Config::load(&self.path)
+    }
+
+    /// Read the file again if it was changed, created or removed since the last read
+    pub fn reload(&mut self) -> Option<io::Result<Config>> {
+        if self.modified() == self.modified {
+            return None;
+        }
+        Some(self.load())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eframe::egui::{Key, Modifiers};
+
+    use super::*;
+    use crate::keymap::KeyCombo;
+
+    #[test]
+    fn empty_file_gives_defaults() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn parses_all_sections() {
+        let config = Config::parse(
+            r##"
+            [defaults]
+            duration = "25m"
+            overtime = true
+            snooze = ["30s", 120]
+
+            [defaults.pomodoro]
+            work = "50m"
+            short_break = 600
+            long_break_every = 2
+
+            [theme]
+            background = "#102030"
+            digits = "#FFFFFF80"
+            pixel_size = 6
+
+            [overlay]
+            width = 200
+            height = 90
+
+            [sounds]
+            volume = 0.5
+            tune = "pager"
+            finished = "/usr/share/sounds/bell.oga"
+            warning = "10s"
+
+            [notifications]
+            enabled = false
+
+            [shortcuts]
+            lap = "Space"
+            toggle = "Ctrl+Space"
+            preset_keys = false
+
+            [hooks]
+            finished = "notify-send done"
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(config.defaults.timer(), TimerDefaults { duration: 1500, overtime: true });
+        assert_eq!(config.defaults.snooze, [Seconds(30), Seconds(120)]);
+        let pomodoro = config.defaults.pomodoro.config();
+        assert_eq!((pomodoro.work, pomodoro.short_break, pomodoro.long_break), (3000, 600, 900));
+        assert_eq!(config.theme.background.0, Color32::from_rgb(0x10, 0x20, 0x30));
+        assert_eq!(config.theme.digits.0, Color32::from_rgba_unmultiplied(255, 255, 255, 0x80));
+        assert_eq!(config.theme.pixels(), PixelStyle { size: 6.0, gap: FULL_PIXELS.gap });
+        assert_eq!(config.overlay.size(), [200.0, 90.0]);
+        assert_eq!(config.sounds.alarm(), AlarmSound::File(PathBuf::from("/usr/share/sounds/bell.oga")));
+        assert_eq!((config.sounds.tune, config.sounds.warning, config.sounds.repeat), (Tune::Pager, Seconds(10), true));
+        assert!(!config.notifications.enabled);
+        assert_eq!(config.shortcuts.lap, KeyCombo::new(Modifiers::NONE, Key::Space));
+        assert_eq!(config.shortcuts.toggle, KeyCombo::new(Modifiers::COMMAND, Key::Space));
+        assert!(!config.shortcuts.preset_keys);
+        assert_eq!(config.hooks.finished.as_deref(), Some("notify-send done"));
+    }
+
+    #[test]
+    fn labels_lengths_in_the_largest_whole_unit() {
+        assert_eq!(Seconds(7200).label(), "2 h");
+        assert_eq!(Seconds(300).label(), "5 min");
+        assert_eq!(Seconds(90).label(), "90 s");
+    }
+
+    #[test]
+    fn errors_name_the_problem() {
+        let error = |text: &str| Config::parse(text).unwrap_err().to_string();
+        assert!(error("[theme]\ndigits = \"red\"").contains("invalid color \"red\""));
+        assert!(error("[defaults]\nduration = \"5x\"").contains("unknown unit 'x'"));
+        assert!(error("[shortcuts]\nlap = \"Hyper\"").contains("unknown key \"Hyper\""));
+        assert!(error("[shortcuts]\nlap = \"R\"").contains("shortcuts.reset and shortcuts.lap are both R"));
+        assert!(error("[theme]\ncolour = \"#000000\"").contains("unknown field `colour`"));
+        assert!(error("[overlay]\nwidth = 10").contains("overlay.width must be between 80 and 2000"));
+        assert!(error("[sounds]\nvolume = 2.0").contains("sounds.volume"));
+        assert!(error("[defaults]\nsnooze = [\"5m\", 0]").contains("defaults.snooze"));
+        assert!(error("[defaults.pomodoro]\nshort_break = 0").contains("defaults.pomodoro.short_break must be longer than 0"));
+        assert!(error("[sounds]\ntune = \"siren\"").contains("unknown variant `siren`"));
+    }
+
+    #[test]
+    fn reloads_when_the_file_changes() {
+        let dir = std::env::temp_dir().join(format!("tiemrzzzz-config-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        let mut file = ConfigFile::new(path.clone());
+        assert_eq!(file.load().unwrap(), Config::default());
+        assert!(file.reload().is_none());
+
+        fs::write(&path, "[defaults]\nduration = \"5m\"\n").unwrap();
+        let config = file.reload().unwrap().unwrap();
+        assert_eq!(config.defaults.duration, Seconds(300));
+        assert!(file.reload().is_none());
+
+        fs::write(&path, "[defaults]\nduration = \"nope\"\n").unwrap();
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert!(file.reload().unwrap().is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(file.reload().unwrap().unwrap(), Config::default());
+    }
+}
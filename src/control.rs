@@ -0,0 +1,619 @@
+use std::io;
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{parse_duration, Args};
+use crate::pomodoro::PomodoroConfig;
+use crate::presets::Presets;
+use crate::sequence::SequenceConfig;
+use crate::timer::{TimerKind, TimerState};
+use crate::timers::{Mode, NamedTimer, TimerList};
+
+/// Startup settings from the command line, forwarded to a running instance
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Launch {
+    /// Timer name, taken from `--preset`
+    pub name: Option<String>,
+    pub duration: Option<u32>,
+    pub stopwatch: bool,
+    pub start: bool,
+    pub overlay: bool,
+}
+
+impl Launch {
+    /// Resolve the command line, looking up `--preset` by name
+    pub fn from_args(args: &Args, presets: &Presets) -> Result<Self, String> {
+        let mut launch = Self {
+            duration: args.duration,
+            stopwatch: args.stopwatch,
+            start: args.start,
+            overlay: args.overlay,
+            ..Self::default()
+        };
+        if let Some(name) = &args.preset {
+            let preset = presets.find(name).ok_or_else(|| format!("No preset named \"{name}\""))?;
+            launch.name = Some(preset.name.clone());
+            launch.duration = Some(preset.seconds);
+        }
+        Ok(launch)
+    }
+
+    /// Whether this asks for a particular timer rather than just raising the window
+    pub fn configures_timer(&self) -> bool {
+        self.duration.is_some() || self.stopwatch
+    }
+
+    /// Set up a timer as asked
+    pub fn apply(&self, entry: &mut NamedTimer) {
+        if self.stopwatch {
+            entry.set_mode(Mode::Stopwatch, &PomodoroConfig::default(), &SequenceConfig::default());
+        } else if let Some(seconds) = self.duration {
+            entry.set_countdown(seconds);
+        }
+        if let Some(name) = &self.name {
+            entry.name = name.clone();
+            entry.preset = Some(name.clone());
+        }
+        if self.start {
+            entry.timer.start();
+        }
+    }
+}
+
+/// A command sent to a running instance, one per line
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Start,
+    Pause,
+    Toggle,
+    Reset,
+    /// Load a stopped countdown of this many seconds
+    Set(u32),
+    /// Add (or with a negative value, take) seconds from the countdown
+    Add(i32),
+    Status,
+    /// Another launch of the app, forwarded as JSON in single-instance mode
+    Launch(Launch),
+}
+
+impl Command {
+    /// Parse a command line such as `set 10m` or `add -30s`
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Err("empty command".to_owned());
+        }
+        let (name, argument) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let (name, argument) = (name.to_ascii_lowercase(), argument.trim());
+        let no_argument = |command| match argument.is_empty() {
+            true => Ok(command),
+            false => Err(format!("'{name}' takes no argument")),
+        };
+
+        match name.as_str() {
+            "start" => no_argument(Command::Start),
+            "pause" => no_argument(Command::Pause),
+            "toggle" => no_argument(Command::Toggle),
+            "reset" => no_argument(Command::Reset),
+            "status" => no_argument(Command::Status),
+            "set" if argument.is_empty() => Err("usage: set DURATION".to_owned()),
+            "set" => parse_duration(argument).map(Command::Set),
+            "add" => {
+                let (sign, amount) = match argument.strip_prefix('-') {
+                    Some(amount) => (-1, amount),
+                    None => (1, argument.strip_prefix('+').unwrap_or(argument)),
+                };
+                if amount.is_empty() {
+                    return Err("usage: add [-]DURATION".to_owned());
+                }
+                parse_duration(amount).map(|seconds| Command::Add(sign * seconds as i32))
+            }
+            "launch" => serde_json::from_str(argument)
+                .map(Command::Launch)
+                .map_err(|err| format!("invalid launch settings: {err}")),
+            _ => Err(format!("unknown command '{name}', expected start, pause, toggle, reset, set, add or status")),
+        }
+    }
+}
+
+/// Snapshot of a timer, sent back with every response
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Status {
+    pub name: String,
+    pub mode: &'static str,
+    pub state: &'static str,
+    /// Countdown length in seconds
+    pub duration: u32,
+    pub remaining: f64,
+    pub elapsed: f64,
+    pub overtime: bool,
+    /// Percent of the countdown used, 0 for a stopwatch
+    pub progress: u8,
+    /// Time as shown on the display, e.g. "04:30"
+    pub display: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phase: Option<String>,
+}
+
+impl Status {
+    pub fn of(entry: &NamedTimer) -> Self {
+        let timer = &entry.timer;
+        let shown = timer.shown().as_secs();
+        let sign = if timer.is_overtime() { "-" } else { "" };
+        let display = match (shown / 3600, (shown / 60) % 60, shown % 60) {
+            (0, minutes, seconds) => format!("{sign}{minutes:02}:{seconds:02}"),
+            (hours, minutes, seconds) => format!("{sign}{hours:02}:{minutes:02}:{seconds:02}"),
+        };
+
+        Self {
+            name: entry.name.clone(),
+            mode: match timer.kind {
+                _ if entry.pomodoro.is_some() => "pomodoro",
+                _ if entry.sequence.is_some() => "intervals",
+                TimerKind::Countdown => "countdown",
+                TimerKind::Stopwatch => "stopwatch",
+            },
+            state: match timer.state {
+                TimerState::Stopped => "stopped",
+                TimerState::Running => "running",
+                TimerState::Paused => "paused",
+            },
+            duration: timer.duration,
+            remaining: timer.remaining().as_secs_f64(),
+            elapsed: timer.elapsed().as_secs_f64(),
+            overtime: timer.is_overtime(),
+            progress: (timer.progress() * 100.0).round() as u8,
+            display,
+            phase: entry.phase_label(),
+        }
+    }
+}
+
+/// Reply to a command, serialized as a single JSON line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    /// Left out by `from_json`; `tiemrzzzz bar` reads it with its own types
+    #[serde(skip_serializing_if = "Option::is_none", skip_deserializing)]
+    pub status: Option<Status>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Response {
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            status: None,
+            error: Some(message.into()),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Read a reply line; `None` when it isn't a response at all
+    pub fn from_json(reply: &str) -> Option<Self> {
+        serde_json::from_str(reply).ok()
+    }
+}
+
+/// Run a command against the primary timer and report its new status
+///
+/// A launch with a duration or `--stopwatch` gets a timer of its own; the
+/// window side of a launch (overlay, focus) is left to the app.
+pub fn execute(command: &Command, timers: &mut TimerList) -> Response {
+    let entry = timers.primary_mut();
+    match command {
+        Command::Start => entry.timer.start(),
+        Command::Pause => entry.timer.pause(),
+        Command::Toggle => entry.timer.toggle(),
+        Command::Reset => entry.reset(),
+        Command::Set(seconds) => entry.set_countdown(*seconds),
+        Command::Add(_) if entry.timer.kind == TimerKind::Stopwatch => {
+            return Response::error("can't add time to a stopwatch");
+        }
+        Command::Add(seconds) => entry.extend(*seconds),
+        Command::Status => {}
+        Command::Launch(launch) => {
+            if launch.configures_timer() {
+                timers.add();
+            }
+            launch.apply(timers.primary_mut());
+        }
+    }
+    let entry = timers.primary();
+    Response {
+        ok: true,
+        status: Some(Status::of(entry)),
+        error: None,
+    }
+}
+
+/// How long a remote caller waits for the app to pick up a command
+const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A command waiting for the app to run it
+pub struct Request {
+    pub command: Command,
+    reply: mpsc::Sender<Response>,
+}
+
+impl Request {
+    pub fn respond(self, response: Response) {
+        let _ = self.reply.send(response);
+    }
+}
+
+/// Handle for control frontends (socket, D-Bus) to run commands in the app
+#[derive(Clone)]
+pub struct Remote {
+    sender: mpsc::Sender<Request>,
+    wake: Arc<dyn Fn() + Send + Sync>,
+}
+
+impl Remote {
+    /// `wake` is called whenever a command arrives so the app can run it
+    pub fn new(wake: impl Fn() + Send + Sync + 'static) -> (Self, mpsc::Receiver<Request>) {
+        let (sender, requests) = mpsc::channel();
+        let remote = Self {
+            sender,
+            wake: Arc::new(wake),
+        };
+        (remote, requests)
+    }
+
+    /// Send a command to the app and wait for its reply
+    pub fn call(&self, command: Command) -> Response {
+        let (reply, response) = mpsc::channel();
+        if self.sender.send(Request { command, reply }).is_err() {
+            return Response::error("the timer has shut down");
+        }
+        (self.wake)();
+        response
+            .recv_timeout(REPLY_TIMEOUT)
+            .unwrap_or_else(|_| Response::error("timed out waiting for the timer"))
+    }
+}
+
+/// Where the control socket lives, e.g. `$XDG_RUNTIME_DIR/tiemrzzzz.sock`, or
+/// `/tmp/tiemrzzzz-1000/tiemrzzzz.sock` when there is no runtime dir
+pub fn socket_path() -> PathBuf {
+    dirs::runtime_dir().unwrap_or_else(private_temp_dir).join("tiemrzzzz.sock")
+}
+
+#[cfg(unix)]
+pub use unix::{create_parent, private_temp_dir, send, ControlServer};
+
+#[cfg(unix)]
+mod unix {
+    use std::fs::{self, DirBuilder};
+    use std::io::{self, BufRead, BufReader, Write};
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::thread;
+
+    use super::{Command, Remote, Response, REPLY_TIMEOUT};
+
+    fn current_uid() -> u32 {
+        // SAFETY: getuid has no preconditions and always succeeds
+        unsafe { libc::getuid() }
+    }
+
+    /// Temp directory of this user's own, e.g. `/tmp/tiemrzzzz-1000`
+    pub fn private_temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("tiemrzzzz-{}", current_uid()))
+    }
+
+    /// Create the directory `path` goes in, readable only by this user
+    pub fn create_parent(path: &Path) -> io::Result<()> {
+        match path.parent() {
+            Some(dir) => DirBuilder::new().recursive(true).mode(0o700).create(dir),
+            None => Ok(()),
+        }
+    }
+
+    /// Refuse a socket set up by another user, who would get to answer our commands
+    fn check_owner(path: &Path) -> io::Result<()> {
+        if fs::metadata(path)?.uid() != current_uid() {
+            let message = format!("{} belongs to another user", path.display());
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, message));
+        }
+        Ok(())
+    }
+
+    /// Listens on the control socket and hands commands to the app; removes the socket when dropped
+    pub struct ControlServer {
+        path: PathBuf,
+    }
+
+    impl ControlServer {
+        pub fn listen(path: &Path, remote: Remote) -> io::Result<Self> {
+            if path.exists() {
+                if UnixStream::connect(path).is_ok() {
+                    return Err(io::Error::new(io::ErrorKind::AddrInUse, "another instance is already listening"));
+                }
+                // Left behind by an instance that didn't exit cleanly
+                fs::remove_file(path)?;
+            }
+            create_parent(path)?;
+            let listener = UnixListener::bind(path)?;
+
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let remote = remote.clone();
+                    thread::spawn(move || serve(stream, &remote));
+                }
+            });
+
+            Ok(Self { path: path.to_owned() })
+        }
+    }
+
+    impl Drop for ControlServer {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+
+    /// Answer each line of a connection until the client hangs up
+    fn serve(stream: UnixStream, remote: &Remote) {
+        let Ok(mut writer) = stream.try_clone() else {
+            return;
+        };
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                return;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let response = match Command::parse(&line) {
+                Ok(command) => remote.call(command),
+                Err(err) => Response::error(err),
+            };
+            if writeln!(writer, "{}", response.to_json()).is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Send one command line to a running instance and return its JSON reply
+    pub fn send(path: &Path, line: &str) -> io::Result<String> {
+        check_owner(path)?;
+        let mut stream = UnixStream::connect(path)?;
+        stream.set_read_timeout(Some(REPLY_TIMEOUT * 2))?;
+        writeln!(stream, "{line}")?;
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply)?;
+        Ok(reply.trim_end().to_owned())
+    }
+}
+
+#[cfg(not(unix))]
+pub use fallback::{create_parent, private_temp_dir, send, ControlServer};
+
+/// Unix domain sockets are not available, so there is nothing to listen on or connect to
+#[cfg(not(unix))]
+mod fallback {
+    use std::fs;
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    use super::Remote;
+
+    pub fn private_temp_dir() -> PathBuf {
+        std::env::temp_dir()
+    }
+
+    pub fn create_parent(path: &Path) -> io::Result<()> {
+        match path.parent() {
+            Some(dir) => fs::create_dir_all(dir),
+            None => Ok(()),
+        }
+    }
+
+    fn unsupported() -> io::Error {
+        io::Error::new(io::ErrorKind::Unsupported, "the control socket is only available on Unix")
+    }
+
+    pub struct ControlServer;
+
+    impl ControlServer {
+        pub fn listen(_path: &Path, _remote: Remote) -> io::Result<Self> {
+            Err(unsupported())
+        }
+    }
+
+    pub fn send(_path: &Path, _line: &str) -> io::Result<String> {
+        Err(unsupported())
+    }
+}
+
+/// Run `tiemrzzzz ctl ...`: send the command and print the reply; returns whether it succeeded
+pub fn run_client(words: &[String]) -> io::Result<bool> {
+    let reply = send(&socket_path(), &words.join(" "))?;
+    println!("{reply}");
+    Ok(Response::from_json(&reply).is_some_and(|response| response.ok))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(Command::parse("start"), Ok(Command::Start));
+        assert_eq!(Command::parse(" Toggle "), Ok(Command::Toggle));
+        assert_eq!(Command::parse("set 10m"), Ok(Command::Set(600)));
+        assert_eq!(Command::parse("set 1h 20m"), Ok(Command::Set(4800)));
+        assert_eq!(Command::parse("add 1m"), Ok(Command::Add(60)));
+        assert_eq!(Command::parse("add +30s"), Ok(Command::Add(30)));
+        assert_eq!(Command::parse("add -1:30"), Ok(Command::Add(-90)));
+    }
+
+    #[test]
+    fn rejects_bad_commands() {
+        for line in ["", "jump", "start now", "set", "set 5x", "add -"] {
+            assert!(Command::parse(line).is_err(), "{line:?} should be rejected");
+        }
+    }
+
+    fn timers() -> TimerList {
+        let mut timers = TimerList::default();
+        timers.rename(0, "Tea");
+        timers
+    }
+
+    #[test]
+    fn executes_against_the_primary_timer() {
+        let mut timers = timers();
+        let response = execute(&Command::Set(210), &mut timers);
+        let status = response.status.unwrap();
+        assert_eq!((status.mode, status.state, status.duration), ("countdown", "stopped", 210));
+        assert_eq!(status.display, "03:30");
+
+        execute(&Command::Add(-30), &mut timers);
+        assert_eq!(timers.primary().timer.duration, 180);
+
+        let status = execute(&Command::Start, &mut timers).status.unwrap();
+        assert_eq!(status.state, "running");
+        assert_eq!(execute(&Command::Toggle, &mut timers).status.unwrap().state, "paused");
+    }
+
+    #[test]
+    fn reset_undoes_added_time() {
+        let mut timers = timers();
+        execute(&Command::Set(300), &mut timers);
+        execute(&Command::Start, &mut timers);
+        execute(&Command::Add(60), &mut timers);
+        assert_eq!(timers.primary().timer.duration, 360);
+        execute(&Command::Reset, &mut timers);
+        assert_eq!(timers.primary().timer.duration, 300);
+    }
+
+    #[test]
+    fn reads_replies_back() {
+        let mut timers = timers();
+        let reply = execute(&Command::Status, &mut timers).to_json();
+        assert!(Response::from_json(&reply).unwrap().ok);
+
+        let reply = Response::error("no such timer").to_json();
+        assert_eq!(Response::from_json(&reply), Some(Response::error("no such timer")));
+        assert_eq!(Response::from_json(r#"{"status":{},"ok":true}"#).map(|response| response.ok), Some(true));
+        assert_eq!(Response::from_json("oops"), None);
+    }
+
+    #[test]
+    fn add_is_refused_for_stopwatch() {
+        let mut timers = timers();
+        timers.primary_mut().timer.set_kind(TimerKind::Stopwatch);
+        let response = execute(&Command::Add(60), &mut timers);
+        assert!(!response.ok);
+    }
+
+    #[test]
+    fn responses_are_single_json_lines() {
+        let json = execute(&Command::Status, &mut timers()).to_json();
+        assert!(json.starts_with(r#"{"ok":true,"status":{"name":"Tea","mode":"countdown","state":"stopped","duration":120,"#));
+        assert!(!json.contains('\n') && !json.contains("error"));
+        assert_eq!(Response::error("nope").to_json(), r#"{"ok":false,"error":"nope"}"#);
+    }
+
+    #[test]
+    fn launch_round_trips_as_a_command() {
+        let launch = Launch {
+            name: Some("Tea  time".to_owned()),
+            duration: Some(210),
+            start: true,
+            ..Launch::default()
+        };
+        let line = format!("launch {}", serde_json::to_string(&launch).unwrap());
+        assert_eq!(Command::parse(&line), Ok(Command::Launch(launch)));
+        assert_eq!(Command::parse("launch {}"), Ok(Command::Launch(Launch::default())));
+        assert!(Command::parse("launch nope").is_err());
+    }
+
+    #[test]
+    fn launch_resolves_presets() {
+        let args = Args {
+            preset: Some("standup".to_owned()),
+            start: true,
+            ..Args::default()
+        };
+        let launch = Launch::from_args(&args, &Presets::default()).unwrap();
+        assert_eq!((launch.name.as_deref(), launch.duration, launch.start), (Some("Standup"), Some(900), true));
+
+        let args = Args {
+            preset: Some("Nap".to_owned()),
+            ..Args::default()
+        };
+        assert!(Launch::from_args(&args, &Presets::default()).is_err());
+    }
+
+    #[test]
+    fn launch_with_a_timer_adds_one() {
+        let mut timers = timers();
+        let launch = Launch {
+            name: Some("Standup".to_owned()),
+            duration: Some(900),
+            start: true,
+            ..Launch::default()
+        };
+        let status = execute(&Command::Launch(launch), &mut timers).status.unwrap();
+        assert_eq!(timers.len(), 2);
+        assert_eq!((status.name.as_str(), status.state, status.duration), ("Standup", "running", 900));
+
+        // A bare launch only raises the window
+        execute(&Command::Launch(Launch::default()), &mut timers);
+        assert_eq!(timers.len(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn socket_round_trip() {
+        let path = std::env::temp_dir().join(format!("tiemrzzzz-test-{}.sock", std::process::id()));
+        let (remote, requests) = Remote::new(|| {});
+        let server = ControlServer::listen(&path, remote.clone()).unwrap();
+        assert_eq!(
+            ControlServer::listen(&path, remote).err().map(|err| err.kind()),
+            Some(io::ErrorKind::AddrInUse)
+        );
+
+        let client = std::thread::spawn({
+            let path = path.clone();
+            move || (send(&path, "set 5m").unwrap(), send(&path, "bogus").unwrap())
+        });
+        let mut timers = timers();
+        let request = requests.recv().unwrap();
+        let response = execute(&request.command, &mut timers);
+        request.respond(response);
+
+        let (set, bogus) = client.join().unwrap();
+        assert!(set.contains(r#""duration":300"#));
+        assert!(bogus.starts_with(r#"{"ok":false,"error":"unknown command"#));
+
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fallback_socket_dir_is_private() {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+        let dir = std::env::temp_dir().join(format!("tiemrzzzz-test-dir-{}", std::process::id()));
+        create_parent(&dir.join("tiemrzzzz.sock")).unwrap();
+        let metadata = std::fs::metadata(&dir).unwrap();
+        std::fs::remove_dir(&dir).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o700);
+        assert!(private_temp_dir().ends_with(format!("tiemrzzzz-{}", metadata.uid())));
+    }
+}
@@ -0,0 +1,300 @@
+/// Well-known name the timer owns on the session bus
+pub const BUS_NAME: &str = "org.tiemrzzzz.Timer";
+pub const OBJECT_PATH: &str = "/org/tiemrzzzz/Timer";
+pub const INTERFACE: &str = "org.tiemrzzzz.Timer1";
+
+#[cfg(target_os = "linux")]
+pub use linux::DbusService;
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::collections::HashMap;
+    use std::io;
+
+    use zbus::blocking::connection;
+    use zbus::names::InterfaceName;
+    use zbus::zvariant::Value;
+    use zbus::{fdo, interface, SignalContext};
+
+    use super::{BUS_NAME, INTERFACE, OBJECT_PATH};
+    use crate::control::{Command, Remote, Response, Status};
+
+    /// The exported object; every call is handed to the app through `remote`
+    struct TimerObject {
+        remote: Remote,
+    }
+
+    impl TimerObject {
+        async fn run(&self, command: Command) -> fdo::Result<Status> {
+            // Wait for the app on a worker thread, so the connection keeps serving other calls
+            let remote = self.remote.clone();
+            match blocking::unblock(move || remote.call(command)).await {
+                Response { ok: true, status: Some(status), .. } => Ok(status),
+                Response { error, .. } => Err(fdo::Error::Failed(error.unwrap_or_else(|| "command failed".to_owned()))),
+            }
+        }
+    }
+
+    #[interface(name = "org.tiemrzzzz.Timer1")]
+    impl TimerObject {
+        async fn start(&self) -> fdo::Result<()> {
+            self.run(Command::Start).await.map(drop)
+        }
+
+        async fn pause(&self) -> fdo::Result<()> {
+            self.run(Command::Pause).await.map(drop)
+        }
+
+        async fn toggle(&self) -> fdo::Result<()> {
+            self.run(Command::Toggle).await.map(drop)
+        }
+
+        async fn reset(&self) -> fdo::Result<()> {
+            self.run(Command::Reset).await.map(drop)
+        }
+
+        /// Turn the timer into a stopped countdown of `seconds`
+        async fn set_duration(&self, seconds: u32) -> fdo::Result<()> {
+            self.run(Command::Set(seconds)).await.map(drop)
+        }
+
+        /// Seconds left on the countdown; changes every frame, so no change signal
+        #[zbus(property(emits_changed_signal = "false"))]
+        async fn remaining(&self) -> fdo::Result<f64> {
+            self.run(Command::Status).await.map(|status| status.remaining)
+        }
+
+        /// "stopped", "running" or "paused"
+        #[zbus(property)]
+        async fn state(&self) -> fdo::Result<String> {
+            self.run(Command::Status).await.map(|status| status.state.to_owned())
+        }
+
+        /// A timer reached zero
+        #[zbus(signal)]
+        async fn finished(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+
+        /// The primary timer was started, paused or stopped
+        #[zbus(signal, name = "StateChanged")]
+        async fn state_changed_signal(ctxt: &SignalContext<'_>, state: &str) -> zbus::Result<()>;
+    }
+
+    /// The timer's object on the session bus; released when dropped
+    pub struct DbusService {
+        _connection: zbus::blocking::Connection,
+        ctxt: SignalContext<'static>,
+        last_state: Option<&'static str>,
+    }
+
+    impl DbusService {
+        /// Export the timer on the session bus
+        pub fn start(remote: Remote) -> io::Result<Self> {
+            Self::serve(connection::Builder::session(), remote)
+        }
+
+        /// Export the timer on the bus at `address`, e.g. a private `dbus-daemon`
+        #[cfg(test)]
+        pub fn start_at(address: &str, remote: Remote) -> io::Result<Self> {
+            Self::serve(connection::Builder::address(address), remote)
+        }
+
+        fn serve(builder: zbus::Result<connection::Builder<'_>>, remote: Remote) -> io::Result<Self> {
+            let connection = builder
+                .and_then(|builder| builder.name(BUS_NAME))
+                .and_then(|builder| builder.serve_at(OBJECT_PATH, TimerObject { remote }))
+                .and_then(|builder| builder.build())
+                .map_err(io::Error::other)?;
+            let ctxt = SignalContext::new(connection.inner(), OBJECT_PATH).map_err(io::Error::other)?;
+            Ok(Self {
+                _connection: connection,
+                ctxt,
+                last_state: None,
+            })
+        }
+
+        /// Emit signals for what changed since the last frame
+        pub fn publish(&mut self, status: &Status, finished: &[String]) -> io::Result<()> {
+            for name in finished {
+                zbus::block_on(TimerObject::finished(&self.ctxt, name)).map_err(io::Error::other)?;
+            }
+            if self.last_state == Some(status.state) {
+                return Ok(());
+            }
+            // The first frame only records the state; nothing has changed yet
+            if self.last_state.replace(status.state).is_none() {
+                return Ok(());
+            }
+
+            zbus::block_on(TimerObject::state_changed_signal(&self.ctxt, status.state)).map_err(io::Error::other)?;
+            let state = Value::from(status.state);
+            let changed = HashMap::from([("State", &state)]);
+            let interface = InterfaceName::from_static_str_unchecked(INTERFACE);
+            zbus::block_on(fdo::Properties::properties_changed(&self.ctxt, interface, &changed, &[]))
+                .map_err(io::Error::other)
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub use fallback::DbusService;
+
+#[cfg(not(target_os = "linux"))]
+mod fallback {
+    use std::io;
+
+    use crate::control::{Remote, Status};
+
+    /// Stand-in where there is no D-Bus
+    pub struct DbusService;
+
+    impl DbusService {
+        pub fn start(_remote: Remote) -> io::Result<Self> {
+            Err(io::Error::new(io::ErrorKind::Unsupported, "D-Bus is only available on Linux"))
+        }
+
+        pub fn publish(&mut self, _status: &Status, _finished: &[String]) -> io::Result<()> {
+            Ok(())
+        }
+    }
+}
+
+/// A private bus for tests
+#[cfg(all(test, target_os = "linux"))]
+pub mod test_bus {
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command as Process, Stdio};
+
+    /// A `dbus-daemon` that goes away with the test
+    pub struct Daemon(Child);
+
+    impl Daemon {
+        /// `None` when `dbus-daemon` isn't installed
+        pub fn spawn() -> Option<(Self, String)> {
+            let mut child = Process::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+            let mut address = String::new();
+            BufReader::new(child.stdout.take()?).read_line(&mut address).ok()?;
+            Some((Self(child), address.trim().to_owned()))
+        }
+    }
+
+    impl Drop for Daemon {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use zbus::blocking::{connection, proxy, Proxy};
+    use zbus::CacheProperties;
+
+    use super::test_bus::Daemon;
+    use super::*;
+    use crate::control::{self, Command, Remote};
+    use crate::timers::TimerList;
+
+    /// Run commands against a timer list on another thread, as the app would
+    fn app() -> Remote {
+        let (remote, requests) = Remote::new(|| {});
+        thread::spawn(move || {
+            let mut timers = TimerList::default();
+            for request in requests {
+                let response = control::execute(&request.command, &mut timers);
+                request.respond(response);
+            }
+        });
+        remote
+    }
+
+    #[test]
+    fn controls_timer_over_private_bus() {
+        let Some((_daemon, address)) = Daemon::spawn() else {
+            eprintln!("dbus-daemon not found, skipping");
+            return;
+        };
+        let remote = app();
+        let mut service = DbusService::start_at(&address, remote.clone()).unwrap();
+
+        let client = connection::Builder::address(address.as_str()).unwrap().build().unwrap();
+        // Read properties straight from the app rather than a cache fed by signals
+        let proxy: Proxy = proxy::Builder::new(&client)
+            .destination(BUS_NAME)
+            .and_then(|builder| builder.path(OBJECT_PATH))
+            .and_then(|builder| builder.interface(INTERFACE))
+            .unwrap()
+            .cache_properties(CacheProperties::No)
+            .build()
+            .unwrap();
+
+        proxy.call_method("SetDuration", &(90u32,)).unwrap();
+        assert_eq!(proxy.get_property::<f64>("Remaining").unwrap(), 90.0);
+        assert_eq!(proxy.get_property::<String>("State").unwrap(), "stopped");
+
+        proxy.call_method("Start", &()).unwrap();
+        assert_eq!(proxy.get_property::<String>("State").unwrap(), "running");
+        proxy.call_method("Pause", &()).unwrap();
+        assert_eq!(proxy.get_property::<String>("State").unwrap(), "paused");
+
+        let mut finished = proxy.receive_signal("Finished").unwrap();
+        let mut state_changed = proxy.receive_signal("StateChanged").unwrap();
+        let status = |command| remote.call(command).status.unwrap();
+        service.publish(&status(control::Command::Status), &[]).unwrap();
+        service.publish(&status(control::Command::Reset), &["Tea".to_owned()]).unwrap();
+
+        let name: String = finished.next().unwrap().body().deserialize().unwrap();
+        assert_eq!(name, "Tea");
+        let state: String = state_changed.next().unwrap().body().deserialize().unwrap();
+        assert_eq!(state, "stopped");
+    }
+
+    #[test]
+    fn serves_other_calls_while_one_waits_for_the_app() {
+        let Some((_daemon, address)) = Daemon::spawn() else {
+            eprintln!("dbus-daemon not found, skipping");
+            return;
+        };
+        let (remote, requests) = Remote::new(|| {});
+        let _service = DbusService::start_at(&address, remote).unwrap();
+        let call = |method: &'static str| {
+            let address = address.clone();
+            thread::spawn(move || {
+                let client = connection::Builder::address(address.as_str()).unwrap().build().unwrap();
+                client.call_method(Some(BUS_NAME), OBJECT_PATH, Some(INTERFACE), method, &()).is_ok()
+            })
+        };
+
+        let start = call("Start");
+        let first = requests.recv_timeout(Duration::from_secs(5)).unwrap();
+        let toggle = call("Toggle");
+        let second = requests.recv_timeout(Duration::from_secs(1)).expect("second call stalled behind the first");
+        assert_eq!((&first.command, &second.command), (&Command::Start, &Command::Toggle));
+
+        let mut timers = TimerList::default();
+        for request in [first, second] {
+            let response = control::execute(&request.command, &mut timers);
+            request.respond(response);
+        }
+        assert!(start.join().unwrap());
+        assert!(toggle.join().unwrap());
+    }
+
+    #[test]
+    fn second_instance_cannot_take_the_name() {
+        let Some((_daemon, address)) = Daemon::spawn() else {
+            eprintln!("dbus-daemon not found, skipping");
+            return;
+        };
+        let _first = DbusService::start_at(&address, app()).unwrap();
+        assert!(DbusService::start_at(&address, app()).is_err());
+    }
+}
@@ -1,12 +1,19 @@
+use std::time::Duration;
+
 use eframe::egui::{self, Color32, Pos2, Rect, Ui};
 
-/// Pixel size for digit rendering
-const PIXEL_SIZE: f32 = 8.0;
-const PIXEL_GAP: f32 = 2.0;
+/// Size of one digit pixel and the gap between pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PixelStyle {
+    pub size: f32,
+    pub gap: f32,
+}
+
+/// Pixels of the full window
+pub const FULL_PIXELS: PixelStyle = PixelStyle { size: 8.0, gap: 2.0 };
 
-/// Smaller pixel size for overlay mode
-const OVERLAY_PIXEL_SIZE: f32 = 5.0;
-const OVERLAY_PIXEL_GAP: f32 = 1.0;
+/// Smaller pixels for overlay mode
+pub const OVERLAY_PIXELS: PixelStyle = PixelStyle { size: 5.0, gap: 1.0 };
 
 /// 5x7 pixel patterns for digits 0-9
 /// Each digit is represented as a 7-row array of 5-bit patterns
@@ -119,19 +126,15 @@ fn draw_pixel_sized(ui: &mut Ui, pos: Pos2, color: Color32, pixel_size: f32) {
     ui.painter().rect_filled(rect, 1.0, color);
 }
 
-/// Draw a digit at the specified position with custom size
-fn draw_digit_sized(ui: &mut Ui, digit: u8, top_left: Pos2, color: Color32, pixel_size: f32, pixel_gap: f32) {
-    if digit > 9 {
-        return;
-    }
-
-    let pattern = DIGIT_PATTERNS[digit as usize];
+/// Draw a digit, minus sign or cursor at the specified position with custom size
+fn draw_glyph_sized(ui: &mut Ui, glyph: Glyph, top_left: Pos2, color: Color32, pixel_size: f32, pixel_gap: f32) {
+    let (pattern, width) = glyph_pattern(glyph);
     let step = pixel_size + pixel_gap;
 
     for (row, &bits) in pattern.iter().enumerate() {
-        for col in 0..5 {
+        for col in 0..width {
             // Check if bit is set (from left to right)
-            if (bits >> (4 - col)) & 1 == 1 {
+            if (bits >> (width - 1 - col)) & 1 == 1 {
                 let x = top_left.x + col as f32 * step;
                 let y = top_left.y + row as f32 * step;
                 draw_pixel_sized(ui, Pos2::new(x, y), color, pixel_size);
@@ -158,53 +161,263 @@ fn digit_width_sized(pixel_size: f32, pixel_gap: f32) -> f32 {
     5.0 * (pixel_size + pixel_gap) - pixel_gap
 }
 
+/// Calculate the width of the minus sign with custom size
+fn minus_width_sized(pixel_size: f32, pixel_gap: f32) -> f32 {
+    3.0 * (pixel_size + pixel_gap) - pixel_gap
+}
+
 /// Calculate the height of a digit with custom size
 fn digit_height_sized(pixel_size: f32, pixel_gap: f32) -> f32 {
     7.0 * (pixel_size + pixel_gap) - pixel_gap
 }
 
-/// Draw the full time display (MM:SS)
-pub fn draw_time(ui: &mut Ui, minutes: u32, seconds: u32, center: Pos2, color: Color32, show_colon: bool, overlay_mode: bool) {
-    let (pixel_size, pixel_gap) = if overlay_mode {
-        (OVERLAY_PIXEL_SIZE, OVERLAY_PIXEL_GAP)
-    } else {
-        (PIXEL_SIZE, PIXEL_GAP)
+/// A time split into the fields shown on the pixel display
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TimeParts {
+    /// Shown with a leading minus (overtime)
+    pub negative: bool,
+    pub hours: u32,
+    pub minutes: u32,
+    pub seconds: u32,
+}
+
+impl From<Duration> for TimeParts {
+    fn from(time: Duration) -> Self {
+        let total = time.as_secs();
+        Self {
+            negative: false,
+            hours: (total / 3600).min(99) as u32,
+            minutes: ((total / 60) % 60) as u32,
+            seconds: (total % 60) as u32,
+        }
+    }
+}
+
+/// What blinks on the display this frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Blink {
+    pub colon: bool,
+    /// Typing cursor after the last digit: `None` when no time is being typed,
+    /// otherwise whether it is lit
+    pub cursor: Option<bool>,
+}
+
+/// A single element of the time layout
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Glyph {
+    Minus,
+    Digit(u8),
+    Colon,
+    /// Full-height bar where the next typed digit goes
+    Cursor,
+}
+
+/// Lay out the time as MM:SS, or HH:MM:SS when there are hours
+fn glyphs(time: TimeParts) -> Vec<Glyph> {
+    let mut glyphs = Vec::with_capacity(9);
+    if time.negative {
+        glyphs.push(Glyph::Minus);
+    }
+    if time.hours > 0 {
+        glyphs.extend([Glyph::Digit((time.hours / 10) as u8), Glyph::Digit((time.hours % 10) as u8), Glyph::Colon]);
+    }
+    glyphs.extend([
+        Glyph::Digit((time.minutes / 10) as u8),
+        Glyph::Digit((time.minutes % 10) as u8),
+        Glyph::Colon,
+        Glyph::Digit((time.seconds / 10) as u8),
+        Glyph::Digit((time.seconds % 10) as u8),
+    ]);
+    glyphs
+}
+
+/// The time as laid out while a time is typed, with the cursor after the digits
+fn typing_glyphs(time: TimeParts) -> Vec<Glyph> {
+    let mut glyphs = glyphs(time);
+    glyphs.push(Glyph::Cursor);
+    glyphs
+}
+
+/// Pixel rows of a glyph (bit 0 is the rightmost column) and its width in pixels
+fn glyph_pattern(glyph: Glyph) -> ([u8; 7], usize) {
+    match glyph {
+        Glyph::Minus => ([0, 0, 0, 0b111, 0, 0, 0], 3),
+        Glyph::Digit(digit) => (DIGIT_PATTERNS[digit.min(9) as usize], 5),
+        Glyph::Colon => ([0, 0, 1, 0, 1, 0, 0], 1),
+        Glyph::Cursor => ([1; 7], 1),
+    }
+}
+
+/// Render the time as seven lines of text, drawing each lit pixel as `pixel`
+pub fn text_rows(time: TimeParts, show_colon: bool, pixel: &str) -> Vec<String> {
+    let blank = " ".repeat(pixel.chars().count());
+    let glyphs = glyphs(time);
+    (0..7)
+        .map(|row| {
+            let mut line = String::new();
+            for (i, &glyph) in glyphs.iter().enumerate() {
+                if i > 0 {
+                    line.push_str(&blank);
+                }
+                let (pattern, width) = glyph_pattern(glyph);
+                let lit = glyph != Glyph::Colon || show_colon;
+                for col in (0..width).rev() {
+                    line.push_str(if lit && (pattern[row] >> col) & 1 == 1 { pixel } else { &blank });
+                }
+            }
+            line
+        })
+        .collect()
+}
+
+/// Draw the full time display (MM:SS or HH:MM:SS), shrunk to fit within `max_width`;
+/// returns the area it covers
+pub fn draw_time(ui: &mut Ui, time: TimeParts, center: Pos2, max_width: f32, color: Color32, blink: Blink, pixels: PixelStyle) -> Rect {
+    let (mut pixel_size, mut pixel_gap) = (pixels.size, pixels.gap);
+
+    let glyphs = if blink.cursor.is_some() { typing_glyphs(time) } else { glyphs(time) };
+    let layout_width = |pixel_size: f32, pixel_gap: f32| {
+        let step = pixel_size + pixel_gap;
+        let colon_width = pixel_size + step; // Single pixel + gap
+        let spacing = step * 1.5;
+        glyphs
+            .iter()
+            .map(|glyph| match glyph {
+                Glyph::Minus => minus_width_sized(pixel_size, pixel_gap),
+                Glyph::Digit(_) => digit_width_sized(pixel_size, pixel_gap),
+                Glyph::Colon => colon_width,
+                Glyph::Cursor => pixel_size,
+            })
+            .sum::<f32>()
+            + (glyphs.len() - 1) as f32 * spacing
     };
-    
+
+    // Scale down when the layout would not fit
+    let natural_width = layout_width(pixel_size, pixel_gap);
+    if natural_width > max_width && max_width > 0.0 {
+        let scale = max_width / natural_width;
+        pixel_size *= scale;
+        pixel_gap *= scale;
+    }
+
     let step = pixel_size + pixel_gap;
     let d_width = digit_width_sized(pixel_size, pixel_gap);
     let d_height = digit_height_sized(pixel_size, pixel_gap);
-    let colon_width = pixel_size + step; // Single pixel + gap
+    let colon_width = pixel_size + step;
     let spacing = step * 1.5;
-    
-    // Total width: 4 digits + 1 colon + spacing
-    let total_width = 4.0 * d_width + colon_width + 4.0 * spacing;
-    let start_x = center.x - total_width / 2.0;
+
+    let total_width = layout_width(pixel_size, pixel_gap);
     let start_y = center.y - d_height / 2.0;
+    let mut x = center.x - total_width / 2.0;
+
+    for glyph in glyphs {
+        match glyph {
+            Glyph::Minus => {
+                draw_glyph_sized(ui, glyph, Pos2::new(x, start_y), color, pixel_size, pixel_gap);
+                x += minus_width_sized(pixel_size, pixel_gap) + spacing;
+            }
+            Glyph::Digit(_) => {
+                draw_glyph_sized(ui, glyph, Pos2::new(x, start_y), color, pixel_size, pixel_gap);
+                x += d_width + spacing;
+            }
+            Glyph::Colon => {
+                draw_colon_sized(ui, Pos2::new(x, start_y), color, blink.colon, pixel_size, pixel_gap);
+                x += colon_width + spacing;
+            }
+            Glyph::Cursor => {
+                if blink.cursor == Some(true) {
+                    draw_glyph_sized(ui, glyph, Pos2::new(x, start_y), color, pixel_size, pixel_gap);
+                }
+                x += pixel_size + spacing;
+            }
+        }
+    }
+
+    Rect::from_min_size(Pos2::new(center.x - total_width / 2.0, start_y), egui::vec2(total_width, d_height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_duration_into_parts() {
+        let time = TimeParts::from(Duration::from_secs(2 * 3600 + 5 * 60 + 9));
+        assert_eq!(time, TimeParts { negative: false, hours: 2, minutes: 5, seconds: 9 });
+    }
 
-    let m1 = (minutes / 10) as u8;
-    let m2 = (minutes % 10) as u8;
-    let s1 = (seconds / 10) as u8;
-    let s2 = (seconds % 10) as u8;
+    #[test]
+    fn hours_are_capped_at_two_digits() {
+        assert_eq!(TimeParts::from(Duration::from_secs(150 * 3600)).hours, 99);
+    }
 
-    let mut x = start_x;
+    #[test]
+    fn uses_minutes_layout_without_hours() {
+        let time = TimeParts { negative: false, hours: 0, minutes: 12, seconds: 34 };
+        assert_eq!(
+            glyphs(time),
+            [Glyph::Digit(1), Glyph::Digit(2), Glyph::Colon, Glyph::Digit(3), Glyph::Digit(4)]
+        );
+    }
 
-    // Minutes tens
-    draw_digit_sized(ui, m1, Pos2::new(x, start_y), color, pixel_size, pixel_gap);
-    x += d_width + spacing;
+    #[test]
+    fn uses_hours_layout_with_hours() {
+        let time = TimeParts { negative: false, hours: 1, minutes: 2, seconds: 3 };
+        assert_eq!(
+            glyphs(time),
+            [
+                Glyph::Digit(0),
+                Glyph::Digit(1),
+                Glyph::Colon,
+                Glyph::Digit(0),
+                Glyph::Digit(2),
+                Glyph::Colon,
+                Glyph::Digit(0),
+                Glyph::Digit(3),
+            ]
+        );
+    }
 
-    // Minutes ones
-    draw_digit_sized(ui, m2, Pos2::new(x, start_y), color, pixel_size, pixel_gap);
-    x += d_width + spacing;
+    #[test]
+    fn renders_text_rows() {
+        let time = TimeParts { negative: true, hours: 0, minutes: 1, seconds: 7 };
+        assert_eq!(
+            text_rows(time, true, "#"),
+            [
+                "     ###    #      ###  #####",
+                "    #   #  ##     #   #     #",
+                "    #  ##   #   # #  ##    # ",
+                "### # # #   #     # # #   #  ",
+                "    ##  #   #   # ##  #  #   ",
+                "    #   #   #     #   #  #   ",
+                "     ###   ###     ###   #   ",
+            ]
+        );
+    }
 
-    // Colon
-    draw_colon_sized(ui, Pos2::new(x, start_y), color, show_colon, pixel_size, pixel_gap);
-    x += colon_width + spacing;
+    #[test]
+    fn hidden_colon_leaves_a_gap() {
+        let time = TimeParts { negative: false, hours: 0, minutes: 0, seconds: 0 };
+        let shown = text_rows(time, true, "#");
+        let hidden = text_rows(time, false, "#");
+        assert_eq!(shown[2].chars().nth(12), Some('#'));
+        assert_eq!(hidden[2].chars().nth(12), Some(' '));
+        assert_eq!(text_rows(time, true, "██")[0].chars().count(), 2 * shown[0].chars().count());
+    }
 
-    // Seconds tens
-    draw_digit_sized(ui, s1, Pos2::new(x, start_y), color, pixel_size, pixel_gap);
-    x += d_width + spacing;
+    #[test]
+    fn typing_puts_the_cursor_last() {
+        let time = TimeParts { negative: false, hours: 0, minutes: 17, seconds: 45 };
+        let glyphs = typing_glyphs(time);
+        assert_eq!(glyphs.len(), 6);
+        assert_eq!(glyphs.last(), Some(&Glyph::Cursor));
+        assert_eq!(glyph_pattern(Glyph::Cursor), ([1; 7], 1));
+    }
 
-    // Seconds ones
-    draw_digit_sized(ui, s2, Pos2::new(x, start_y), color, pixel_size, pixel_gap);
+    #[test]
+    fn negative_time_leads_with_minus() {
+        let time = TimeParts { negative: true, hours: 0, minutes: 1, seconds: 5 };
+        assert_eq!(glyphs(time)[0], Glyph::Minus);
+        assert_eq!(glyphs(time).len(), 6);
+    }
 }
@@ -0,0 +1,132 @@
+use std::io;
+use std::process::{Command as Process, Stdio};
+use std::thread;
+
+use serde::Deserialize;
+
+use crate::control::Status;
+use crate::timer::TimerEvent;
+use crate::timers::NamedTimer;
+
+/// Shell commands to run on timer events, from the `[hooks]` table of the config file
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Hooks {
+    pub started: Option<String>,
+    pub paused: Option<String>,
+    pub resumed: Option<String>,
+    pub reset: Option<String>,
+    pub finished: Option<String>,
+    pub duration_changed: Option<String>,
+    pub phase_changed: Option<String>,
+}
+
+impl Hooks {
+    fn command(&self, event: TimerEvent) -> Option<&str> {
+        let command = match event {
+            TimerEvent::Started => &self.started,
+            TimerEvent::Paused => &self.paused,
+            TimerEvent::Resumed => &self.resumed,
+            TimerEvent::Reset => &self.reset,
+            TimerEvent::Finished => &self.finished,
+            TimerEvent::DurationChanged => &self.duration_changed,
+            TimerEvent::PhaseChanged => &self.phase_changed,
+            // Cues for the countdown beeps, too frequent to run scripts for
+            TimerEvent::SegmentEnded | TimerEvent::CountdownSecond => return None,
+        };
+        command.as_deref().filter(|command| !command.trim().is_empty())
+    }
+
+    /// The shell command for `event`, with the timer described in its environment
+    fn process(&self, event: TimerEvent, entry: &NamedTimer) -> Option<Process> {
+        let command = self.command(event)?;
+        #[cfg(windows)]
+        let mut process = {
+            let mut process = Process::new("cmd");
+            process.arg("/C");
+            process
+        };
+        #[cfg(not(windows))]
+        let mut process = {
+            let mut process = Process::new("sh");
+            process.arg("-c");
+            process
+        };
+        process.arg(command).envs(environment(event, entry)).stdin(Stdio::null());
+        Some(process)
+    }
+
+    /// Run the hook for `event` in the background, if there is one
+    pub fn run(&self, event: TimerEvent, entry: &NamedTimer) -> io::Result<()> {
+        let Some(mut process) = self.process(event, entry) else {
+            return Ok(());
+        };
+        let mut child = process.spawn()?;
+        // Reap the hook when it exits
+        thread::spawn(move || child.wait());
+        Ok(())
+    }
+}
+
+/// `TIEMRZZZZ_*` variables describing the event; times are whole seconds
+fn environment(event: TimerEvent, entry: &NamedTimer) -> Vec<(&'static str, String)> {
+    let status = Status::of(entry);
+    let mut env = vec![
+        ("TIEMRZZZZ_EVENT", event.name().to_owned()),
+        ("TIEMRZZZZ_TIMER", status.name),
+        ("TIEMRZZZZ_MODE", status.mode.to_owned()),
+        ("TIEMRZZZZ_STATE", status.state.to_owned()),
+        ("TIEMRZZZZ_DURATION", status.duration.to_string()),
+        ("TIEMRZZZZ_ELAPSED", (status.elapsed.round() as u64).to_string()),
+        ("TIEMRZZZZ_REMAINING", (status.remaining.round() as u64).to_string()),
+    ];
+    if let Some(phase) = status.phase {
+        env.push(("TIEMRZZZZ_PHASE", phase));
+    }
+    if let Some(preset) = &entry.preset {
+        env.push(("TIEMRZZZZ_PRESET", preset.clone()));
+    }
+    env
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::presets::Preset;
+
+    #[test]
+    fn parses_hooks() {
+        let hooks: Hooks = toml::from_str("finished = \"notify-send done\"\nstarted = \"  \"\n").unwrap();
+        assert_eq!(hooks.command(TimerEvent::Finished), Some("notify-send done"));
+        assert_eq!(hooks.command(TimerEvent::Started), None);
+        assert_eq!(hooks.command(TimerEvent::Paused), None);
+        assert!(toml::from_str::<Hooks>("finish = \"typo\"").is_err());
+    }
+
+    #[test]
+    fn describes_the_timer() {
+        let mut entry = NamedTimer::new("Kitchen");
+        entry.load_preset(&Preset::new("Tea", 210));
+        let env: HashMap<_, _> = environment(TimerEvent::Started, &entry).into_iter().collect();
+        assert_eq!(env["TIEMRZZZZ_EVENT"], "started");
+        assert_eq!(env["TIEMRZZZZ_TIMER"], "Kitchen");
+        assert_eq!(env["TIEMRZZZZ_DURATION"], "210");
+        assert_eq!(env["TIEMRZZZZ_ELAPSED"], "0");
+        assert_eq!(env["TIEMRZZZZ_PRESET"], "Tea");
+        assert!(!env.contains_key("TIEMRZZZZ_PHASE"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn runs_shell_command() {
+        let hooks = Hooks {
+            finished: Some("echo \"$TIEMRZZZZ_EVENT $TIEMRZZZZ_TIMER\"".to_owned()),
+            ..Hooks::default()
+        };
+        let mut process = hooks.process(TimerEvent::Finished, &NamedTimer::new("Tea")).unwrap();
+        let output = process.stdout(Stdio::piped()).output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "finished Tea\n");
+    }
+}
@@ -0,0 +1,65 @@
+use std::fs::{File, TryLockError};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::control::{self, Launch};
+
+/// How long to keep trying to reach an instance that is still starting up
+const FORWARD_ATTEMPTS: u32 = 20;
+const FORWARD_RETRY: Duration = Duration::from_millis(100);
+
+/// Held by the one running instance; released when dropped or when the process exits
+pub struct InstanceLock {
+    _file: File,
+}
+
+/// Lock file next to the control socket, e.g. `$XDG_RUNTIME_DIR/tiemrzzzz.lock`
+pub fn lock_path() -> PathBuf {
+    control::socket_path().with_extension("lock")
+}
+
+/// Take the instance lock, or `None` if another instance already holds it
+pub fn try_lock(path: &Path) -> io::Result<Option<InstanceLock>> {
+    control::create_parent(path)?;
+    let file = File::options().create(true).truncate(false).write(true).open(path)?;
+    match file.try_lock() {
+        Ok(()) => Ok(Some(InstanceLock { _file: file })),
+        Err(TryLockError::WouldBlock) => Ok(None),
+        Err(TryLockError::Error(err)) => Err(err),
+    }
+}
+
+/// Hand a launch to the running instance, waiting briefly if it is still starting up
+pub fn forward(launch: &Launch) -> io::Result<String> {
+    let line = format!("launch {}", serde_json::to_string(launch).map_err(io::Error::other)?);
+    let mut attempts = 0;
+    loop {
+        match control::send(&control::socket_path(), &line) {
+            Ok(reply) => return Ok(reply),
+            Err(err) if attempts + 1 >= FORWARD_ATTEMPTS || err.kind() == io::ErrorKind::Unsupported => return Err(err),
+            Err(_) => {
+                attempts += 1;
+                thread::sleep(FORWARD_RETRY);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_one_holder_at_a_time() {
+        let path = std::env::temp_dir().join(format!("tiemrzzzz-test-{}.lock", std::process::id()));
+        let lock = try_lock(&path).unwrap();
+        assert!(lock.is_some());
+        assert!(try_lock(&path).unwrap().is_none());
+
+        drop(lock);
+        assert!(try_lock(&path).unwrap().is_some());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
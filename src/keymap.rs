@@ -0,0 +1,310 @@
+use std::fmt;
+
+use eframe::egui::{InputState, Key, KeyboardShortcut, ModifierNames, Modifiers};
+use serde::Deserialize;
+
+/// Something the keyboard can do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shortcut {
+    Toggle,
+    Reset,
+    Overlay,
+    Lap,
+    EditTime,
+    AddMinute,
+    SubtractMinute,
+    AddTenMinutes,
+    SubtractTenMinutes,
+    AddSecond,
+    SubtractSecond,
+    AddTenSeconds,
+    SubtractTenSeconds,
+    Help,
+    Quit,
+}
+
+impl Shortcut {
+    pub const ALL: [Shortcut; 15] = [
+        Shortcut::Toggle,
+        Shortcut::Reset,
+        Shortcut::Overlay,
+        Shortcut::Lap,
+        Shortcut::EditTime,
+        Shortcut::AddMinute,
+        Shortcut::SubtractMinute,
+        Shortcut::AddTenMinutes,
+        Shortcut::SubtractTenMinutes,
+        Shortcut::AddSecond,
+        Shortcut::SubtractSecond,
+        Shortcut::AddTenSeconds,
+        Shortcut::SubtractTenSeconds,
+        Shortcut::Help,
+        Shortcut::Quit,
+    ];
+
+    /// Key in the `[shortcuts]` table
+    pub fn name(self) -> &'static str {
+        match self {
+            Shortcut::Toggle => "toggle",
+            Shortcut::Reset => "reset",
+            Shortcut::Overlay => "overlay",
+            Shortcut::Lap => "lap",
+            Shortcut::EditTime => "edit_time",
+            Shortcut::AddMinute => "add_minute",
+            Shortcut::SubtractMinute => "subtract_minute",
+            Shortcut::AddTenMinutes => "add_ten_minutes",
+            Shortcut::SubtractTenMinutes => "subtract_ten_minutes",
+            Shortcut::AddSecond => "add_second",
+            Shortcut::SubtractSecond => "subtract_second",
+            Shortcut::AddTenSeconds => "add_ten_seconds",
+            Shortcut::SubtractTenSeconds => "subtract_ten_seconds",
+            Shortcut::Help => "help",
+            Shortcut::Quit => "quit",
+        }
+    }
+
+    /// What it does, for the help window
+    pub fn description(self) -> &'static str {
+        match self {
+            Shortcut::Toggle => "Start, pause or resume",
+            Shortcut::Reset => "Reset",
+            Shortcut::Overlay => "Focus overlay on or off",
+            Shortcut::Lap => "Record a lap",
+            Shortcut::EditTime => "Type a time",
+            Shortcut::AddMinute => "Add a minute",
+            Shortcut::SubtractMinute => "Take off a minute",
+            Shortcut::AddTenMinutes => "Add ten minutes",
+            Shortcut::SubtractTenMinutes => "Take off ten minutes",
+            Shortcut::AddSecond => "Add a second",
+            Shortcut::SubtractSecond => "Take off a second",
+            Shortcut::AddTenSeconds => "Add ten seconds",
+            Shortcut::SubtractTenSeconds => "Take off ten seconds",
+            Shortcut::Help => "Show these shortcuts",
+            Shortcut::Quit => "Quit",
+        }
+    }
+
+    /// Seconds added to the countdown, for the adjusting shortcuts
+    pub fn adjustment(self) -> Option<i32> {
+        match self {
+            Shortcut::AddMinute => Some(60),
+            Shortcut::SubtractMinute => Some(-60),
+            Shortcut::AddTenMinutes => Some(600),
+            Shortcut::SubtractTenMinutes => Some(-600),
+            Shortcut::AddSecond => Some(1),
+            Shortcut::SubtractSecond => Some(-1),
+            Shortcut::AddTenSeconds => Some(10),
+            Shortcut::SubtractTenSeconds => Some(-10),
+            _ => None,
+        }
+    }
+}
+
+/// A key with modifiers, e.g. "Space", "Shift+Up" or "Ctrl+Q"
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeyCombo(pub KeyboardShortcut);
+
+impl KeyCombo {
+    pub const fn new(modifiers: Modifiers, key: Key) -> Self {
+        Self(KeyboardShortcut::new(modifiers, key))
+    }
+
+    /// Number of modifiers held, so more specific combos can be checked first
+    fn modifier_count(&self) -> usize {
+        let modifiers = self.0.modifiers;
+        [modifiers.alt, modifiers.shift, modifiers.ctrl || modifiers.command, modifiers.mac_cmd]
+            .into_iter()
+            .filter(|&held| held)
+            .count()
+    }
+}
+
+impl TryFrom<String> for KeyCombo {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        let unknown = || format!("unknown key \"{text}\", use names like L, Space, Shift+Up or Ctrl+Q");
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        // "Ctrl++" binds the plus key
+        if parts.len() > 1 && parts[parts.len() - 2..] == ["", ""] {
+            parts.truncate(parts.len() - 2);
+            parts.push("+");
+        }
+        let key = parts.pop().and_then(Key::from_name).ok_or_else(unknown)?;
+        let mut modifiers = Modifiers::NONE;
+        for part in parts {
+            modifiers = modifiers
+                | match part.to_ascii_lowercase().as_str() {
+                    "ctrl" | "control" | "cmd" | "command" => Modifiers::COMMAND,
+                    "shift" => Modifiers::SHIFT,
+                    "alt" | "option" => Modifiers::ALT,
+                    _ => return Err(unknown()),
+                };
+        }
+        Ok(Self::new(modifiers, key))
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.format(&ModifierNames::NAMES, cfg!(target_os = "macos")))
+    }
+}
+
+/// Keys for the main window, from the `[shortcuts]` table of the config file
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Keymap {
+    pub toggle: KeyCombo,
+    pub reset: KeyCombo,
+    pub overlay: KeyCombo,
+    /// Record a stopwatch lap
+    pub lap: KeyCombo,
+    pub edit_time: KeyCombo,
+    pub add_minute: KeyCombo,
+    pub subtract_minute: KeyCombo,
+    pub add_ten_minutes: KeyCombo,
+    pub subtract_ten_minutes: KeyCombo,
+    pub add_second: KeyCombo,
+    pub subtract_second: KeyCombo,
+    pub add_ten_seconds: KeyCombo,
+    pub subtract_ten_seconds: KeyCombo,
+    pub help: KeyCombo,
+    pub quit: KeyCombo,
+    /// Load presets with the number keys 1-9
+    pub preset_keys: bool,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            toggle: KeyCombo::new(Modifiers::NONE, Key::Space),
+            reset: KeyCombo::new(Modifiers::NONE, Key::R),
+            overlay: KeyCombo::new(Modifiers::NONE, Key::F),
+            lap: KeyCombo::new(Modifiers::NONE, Key::L),
+            edit_time: KeyCombo::new(Modifiers::NONE, Key::Enter),
+            add_minute: KeyCombo::new(Modifiers::NONE, Key::ArrowUp),
+            subtract_minute: KeyCombo::new(Modifiers::NONE, Key::ArrowDown),
+            add_ten_minutes: KeyCombo::new(Modifiers::SHIFT, Key::ArrowUp),
+            subtract_ten_minutes: KeyCombo::new(Modifiers::SHIFT, Key::ArrowDown),
+            add_second: KeyCombo::new(Modifiers::NONE, Key::ArrowRight),
+            subtract_second: KeyCombo::new(Modifiers::NONE, Key::ArrowLeft),
+            add_ten_seconds: KeyCombo::new(Modifiers::SHIFT, Key::ArrowRight),
+            subtract_ten_seconds: KeyCombo::new(Modifiers::SHIFT, Key::ArrowLeft),
+            help: KeyCombo::new(Modifiers::NONE, Key::F1),
+            quit: KeyCombo::new(Modifiers::COMMAND, Key::Q),
+            preset_keys: true,
+        }
+    }
+}
+
+impl Keymap {
+    pub fn key(&self, shortcut: Shortcut) -> KeyCombo {
+        match shortcut {
+            Shortcut::Toggle => self.toggle,
+            Shortcut::Reset => self.reset,
+            Shortcut::Overlay => self.overlay,
+            Shortcut::Lap => self.lap,
+            Shortcut::EditTime => self.edit_time,
+            Shortcut::AddMinute => self.add_minute,
+            Shortcut::SubtractMinute => self.subtract_minute,
+            Shortcut::AddTenMinutes => self.add_ten_minutes,
+            Shortcut::SubtractTenMinutes => self.subtract_ten_minutes,
+            Shortcut::AddSecond => self.add_second,
+            Shortcut::SubtractSecond => self.subtract_second,
+            Shortcut::AddTenSeconds => self.add_ten_seconds,
+            Shortcut::SubtractTenSeconds => self.subtract_ten_seconds,
+            Shortcut::Help => self.help,
+            Shortcut::Quit => self.quit,
+        }
+    }
+
+    /// Shortcuts pressed this frame, taking their key presses out of `input`
+    pub fn pressed(&self, input: &mut InputState) -> Vec<Shortcut> {
+        let mut shortcuts = Shortcut::ALL;
+        // Shift+Up must be checked before Up, which also matches with Shift held
+        shortcuts.sort_by_key(|&shortcut| std::cmp::Reverse(self.key(shortcut).modifier_count()));
+        shortcuts
+            .into_iter()
+            .filter(|&shortcut| input.consume_shortcut(&self.key(shortcut).0))
+            .collect()
+    }
+
+    /// Check that no key does two things
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, a) in Shortcut::ALL.into_iter().enumerate() {
+            let key = self.key(a);
+            if let Some(b) = Shortcut::ALL[i + 1..].iter().find(|&&b| self.key(b) == key) {
+                return Err(format!("shortcuts.{} and shortcuts.{} are both {key}", a.name(), b.name()));
+            }
+            let digit = DIGIT_KEYS.contains(&key.0.logical_key) && key.0.modifiers.is_none();
+            if digit && self.preset_keys {
+                return Err(format!("shortcuts.{} is {key}, which loads a preset; set preset_keys = false to use it", a.name()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The number keys 0-9
+pub const DIGIT_KEYS: [Key; 10] = [
+    Key::Num0,
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+];
+
+#[cfg(test)]
+mod tests {
+    use eframe::egui::{Event, Options, RawInput};
+
+    use super::*;
+
+    fn combo(text: &str) -> Result<KeyCombo, String> {
+        KeyCombo::try_from(text.to_owned())
+    }
+
+    fn press(modifiers: Modifiers, key: Key) -> InputState {
+        let event = Event::Key { key, physical_key: None, pressed: true, repeat: false, modifiers };
+        let raw = RawInput { events: vec![event], modifiers, ..RawInput::default() };
+        InputState::default().begin_pass(raw, false, 1.0, &Options::default())
+    }
+
+    #[test]
+    fn parses_key_combos() {
+        assert_eq!(combo("Space"), Ok(KeyCombo::new(Modifiers::NONE, Key::Space)));
+        assert_eq!(combo("shift + Up"), Ok(KeyCombo::new(Modifiers::SHIFT, Key::ArrowUp)));
+        assert_eq!(combo("Ctrl+Alt+Q"), Ok(KeyCombo::new(Modifiers::COMMAND | Modifiers::ALT, Key::Q)));
+        assert_eq!(combo("Ctrl++"), Ok(KeyCombo::new(Modifiers::COMMAND, Key::Plus)));
+        assert!(combo("Hyper+Q").unwrap_err().contains("unknown key \"Hyper+Q\""));
+        assert!(combo("").is_err());
+        #[cfg(not(target_os = "macos"))]
+        assert_eq!(combo("shift+ctrl+q").unwrap().to_string(), "Ctrl+Shift+Q");
+    }
+
+    #[test]
+    fn shift_picks_the_bigger_step() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.pressed(&mut press(Modifiers::SHIFT, Key::ArrowUp)), [Shortcut::AddTenMinutes]);
+        assert_eq!(keymap.pressed(&mut press(Modifiers::NONE, Key::ArrowUp)), [Shortcut::AddMinute]);
+        assert_eq!(keymap.pressed(&mut press(Modifiers::COMMAND, Key::Q)), [Shortcut::Quit]);
+        assert!(keymap.pressed(&mut press(Modifiers::NONE, Key::Q)).is_empty());
+    }
+
+    #[test]
+    fn rejects_clashing_keys() {
+        assert_eq!(Keymap::default().validate(), Ok(()));
+        let keymap = Keymap { lap: combo("Space").unwrap(), ..Keymap::default() };
+        assert_eq!(keymap.validate().unwrap_err(), "shortcuts.toggle and shortcuts.lap are both Space");
+        let keymap = Keymap { reset: combo("5").unwrap(), ..Keymap::default() };
+        assert!(keymap.validate().unwrap_err().contains("loads a preset"));
+        assert_eq!(Keymap { preset_keys: false, ..keymap }.validate(), Ok(()));
+    }
+}
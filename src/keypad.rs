@@ -0,0 +1,146 @@
+use eframe::egui::{Event, Key};
+
+use crate::display::TimeParts;
+use crate::keymap::DIGIT_KEYS;
+use crate::timer::MAX_DURATION;
+
+/// Digits of HH:MM:SS
+const MAX_DIGITS: usize = 6;
+
+/// A time typed like on a microwave keypad: each digit shifts in from the right,
+/// so 1, 7, 4, 5 gives 17:45
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimeEntry {
+    digits: Vec<u8>,
+}
+
+impl TimeEntry {
+    /// Add a digit on the right; leading zeros and digits past HH:MM:SS are ignored
+    pub fn push(&mut self, digit: u8) {
+        if digit <= 9 && self.digits.len() < MAX_DIGITS && !(digit == 0 && self.digits.is_empty()) {
+            self.digits.push(digit);
+        }
+    }
+
+    /// Take back the last digit
+    pub fn pop(&mut self) {
+        self.digits.pop();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    /// The digits as typed, e.g. 1, 7, 4, 5 as 00:17:45; minutes and seconds may be over 59
+    pub fn parts(&self) -> TimeParts {
+        let pair = |from_right: usize| {
+            let digit = |i: usize| self.digits.len().checked_sub(i + 1).map_or(0, |i| u32::from(self.digits[i]));
+            digit(from_right + 1) * 10 + digit(from_right)
+        };
+        TimeParts {
+            negative: false,
+            hours: pair(4),
+            minutes: pair(2),
+            seconds: pair(0),
+        }
+    }
+
+    /// The length typed in seconds, so 90 is a minute and a half
+    pub fn seconds(&self) -> u32 {
+        let parts = self.parts();
+        (parts.hours * 3600 + parts.minutes * 60 + parts.seconds).min(MAX_DURATION)
+    }
+}
+
+/// A key press that edits a typed time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TypingKey {
+    Digit(u8),
+    Backspace,
+    Enter,
+    Escape,
+}
+
+/// Take this frame's typing keys out of `events`, in the order they were pressed,
+/// so several digits typed within one frame all count
+pub fn take_typing_keys(events: &mut Vec<Event>) -> Vec<TypingKey> {
+    let mut keys = Vec::new();
+    events.retain(|event| {
+        let Event::Key { key, pressed: true, modifiers, .. } = event else {
+            return true;
+        };
+        let typed = match key {
+            _ if !modifiers.is_none() => None,
+            Key::Backspace => Some(TypingKey::Backspace),
+            Key::Enter => Some(TypingKey::Enter),
+            Key::Escape => Some(TypingKey::Escape),
+            key => DIGIT_KEYS.iter().position(|digit| digit == key).map(|digit| TypingKey::Digit(digit as u8)),
+        };
+        keys.extend(typed);
+        typed.is_none()
+    });
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use eframe::egui::Modifiers;
+
+    use super::*;
+
+    fn typed(digits: &[u8]) -> TimeEntry {
+        let mut entry = TimeEntry::default();
+        digits.iter().for_each(|&digit| entry.push(digit));
+        entry
+    }
+
+    #[test]
+    fn digits_shift_in_from_the_right() {
+        let entry = typed(&[1, 7, 4, 5]);
+        assert_eq!((entry.parts().minutes, entry.parts().seconds), (17, 45));
+        assert_eq!(entry.seconds(), 17 * 60 + 45);
+        assert_eq!(typed(&[9, 0]).seconds(), 90);
+        assert_eq!(typed(&[1, 2, 3, 4, 5, 6, 7]).parts().hours, 12, "only six digits fit");
+        assert_eq!(typed(&[9, 9, 9, 9, 9, 9]).seconds(), MAX_DURATION);
+    }
+
+    #[test]
+    fn ignores_leading_zeros_and_takes_back_digits() {
+        let mut entry = typed(&[0, 0, 5]);
+        assert_eq!(entry.seconds(), 5);
+        entry.push(0);
+        entry.pop();
+        entry.pop();
+        assert!(entry.is_empty());
+    }
+
+    #[test]
+    fn takes_every_key_typed_in_a_frame() {
+        let press = |key, modifiers| Event::Key { key, physical_key: None, pressed: true, repeat: false, modifiers };
+        let mut events = vec![
+            press(Key::Num1, Modifiers::NONE),
+            Event::Text("1".to_owned()),
+            press(Key::Num7, Modifiers::NONE),
+            press(Key::Num4, Modifiers::NONE),
+            press(Key::Num4, Modifiers::NONE),
+            press(Key::Backspace, Modifiers::NONE),
+            press(Key::Num5, Modifiers::CTRL),
+            press(Key::Num5, Modifiers::NONE),
+            press(Key::Enter, Modifiers::NONE),
+        ];
+        let keys = take_typing_keys(&mut events);
+        assert_eq!(
+            keys,
+            [
+                TypingKey::Digit(1),
+                TypingKey::Digit(7),
+                TypingKey::Digit(4),
+                TypingKey::Digit(4),
+                TypingKey::Backspace,
+                TypingKey::Digit(5),
+                TypingKey::Enter,
+            ]
+        );
+        assert_eq!(events, [Event::Text("1".to_owned()), press(Key::Num5, Modifiers::CTRL)]);
+    }
+}
@@ -0,0 +1,163 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A single recorded lap
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lap {
+    /// Time since the previous lap
+    pub split: Duration,
+    /// Time since the stopwatch started
+    pub total: Duration,
+}
+
+/// Laps recorded for a stopwatch run
+#[derive(Debug, Default)]
+pub struct Laps {
+    laps: Vec<Lap>,
+}
+
+impl Laps {
+    /// Record a lap at the given stopwatch time
+    pub fn record(&mut self, total: Duration) {
+        let previous = self.laps.last().map_or(Duration::ZERO, |lap| lap.total);
+        self.laps.push(Lap {
+            split: total.saturating_sub(previous),
+            total,
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.laps.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.laps.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &[Lap] {
+        &self.laps
+    }
+
+    /// Index of the fastest lap (only meaningful with two or more laps)
+    pub fn best(&self) -> Option<usize> {
+        if self.laps.len() < 2 {
+            return None;
+        }
+        self.laps.iter().enumerate().min_by_key(|(_, lap)| lap.split).map(|(i, _)| i)
+    }
+
+    /// Index of the slowest lap (only meaningful with two or more laps)
+    pub fn worst(&self) -> Option<usize> {
+        if self.laps.len() < 2 {
+            return None;
+        }
+        self.laps.iter().enumerate().max_by_key(|(_, lap)| lap.split).map(|(i, _)| i)
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("lap,split,total,split_seconds,total_seconds\n");
+        for (i, lap) in self.laps.iter().enumerate() {
+            csv.push_str(&format!(
+                "{},{},{},{:.3},{:.3}\n",
+                i + 1,
+                format_lap_time(lap.split),
+                format_lap_time(lap.total),
+                lap.split.as_secs_f64(),
+                lap.total.as_secs_f64(),
+            ));
+        }
+        csv
+    }
+
+    /// Write the laps to a timestamped CSV file in `dir`
+    pub fn export_csv(&self, dir: &Path) -> io::Result<PathBuf> {
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("laps-{stamp}.csv"));
+        fs::write(&path, self.to_csv())?;
+        Ok(path)
+    }
+}
+
+/// Format a lap time as MM:SS.cc, or H:MM:SS.cc past an hour
+pub fn format_lap_time(time: Duration) -> String {
+    let total = time.as_secs();
+    let centis = time.subsec_millis() / 10;
+    let (hours, minutes, seconds) = (total / 3600, (total / 60) % 60, total % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}.{centis:02}")
+    } else {
+        format!("{minutes:02}:{seconds:02}.{centis:02}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn laps(totals: &[u64]) -> Laps {
+        let mut laps = Laps::default();
+        for &total in totals {
+            laps.record(ms(total));
+        }
+        laps
+    }
+
+    #[test]
+    fn records_split_and_cumulative_times() {
+        let laps = laps(&[10_000, 25_500, 31_000]);
+        let splits: Vec<_> = laps.as_slice().iter().map(|lap| lap.split).collect();
+        let totals: Vec<_> = laps.as_slice().iter().map(|lap| lap.total).collect();
+        assert_eq!(splits, [ms(10_000), ms(15_500), ms(5_500)]);
+        assert_eq!(totals, [ms(10_000), ms(25_500), ms(31_000)]);
+    }
+
+    #[test]
+    fn best_and_worst_need_two_laps() {
+        let laps = laps(&[10_000]);
+        assert_eq!(laps.best(), None);
+        assert_eq!(laps.worst(), None);
+    }
+
+    #[test]
+    fn finds_best_and_worst_laps() {
+        let laps = laps(&[10_000, 25_500, 31_000]);
+        assert_eq!(laps.best(), Some(2));
+        assert_eq!(laps.worst(), Some(1));
+    }
+
+    #[test]
+    fn clear_removes_all_laps() {
+        let mut laps = laps(&[1_000, 2_000]);
+        laps.clear();
+        assert!(laps.is_empty());
+        laps.record(ms(5_000));
+        assert_eq!(laps.as_slice()[0].split, ms(5_000));
+    }
+
+    #[test]
+    fn formats_lap_times() {
+        assert_eq!(format_lap_time(ms(65_432)), "01:05.43");
+        assert_eq!(format_lap_time(ms(3_723_050)), "1:02:03.05");
+    }
+
+    #[test]
+    fn exports_csv() {
+        let laps = laps(&[10_000, 25_500]);
+        assert_eq!(
+            laps.to_csv(),
+            "lap,split,total,split_seconds,total_seconds\n\
+             1,00:10.00,00:10.00,10.000,10.000\n\
+             2,00:15.50,00:25.50,15.500,25.500\n"
+        );
+    }
+}
@@ -1,27 +1,154 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // Hide console on Windows in release
 
+mod alarm;
 mod app;
+mod bar;
+mod cli;
+mod config;
+mod control;
+mod dbus;
 mod display;
+mod hooks;
+mod instance;
+mod keymap;
+mod keypad;
+mod laps;
+mod notify;
+mod pomodoro;
+mod presets;
+mod sequence;
+mod session;
+mod sound;
 mod timer;
+mod timers;
+mod tui;
 
 use app::TimerApp;
+use clap::Parser;
+use cli::{Action, Args};
+use config::{Config, ConfigFile, Overlay};
+use control::{Launch, Response};
 use eframe::egui;
+use instance::InstanceLock;
+use presets::Presets;
+use session::Session;
+
+/// In single-instance mode, forward the launch to a running instance and exit;
+/// returns the instance lock when this is the first instance
+fn claim_instance(args: &Args) -> Option<InstanceLock> {
+    match instance::try_lock(&instance::lock_path()) {
+        Ok(Some(lock)) => return Some(lock),
+        Ok(None) => {}
+        Err(err) => {
+            eprintln!("tiemrzzzz: couldn't check for a running instance: {err}");
+            return None;
+        }
+    }
+
+    let presets = Presets::default_path()
+        .and_then(|path| Presets::load(&path).ok())
+        .unwrap_or_default();
+    let launch = Launch::from_args(args, &presets).unwrap_or_else(|err| {
+        eprintln!("tiemrzzzz: {err}");
+        std::process::exit(2);
+    });
+    match instance::forward(&launch).map(|reply| Response::from_json(&reply)) {
+        Ok(Some(Response { ok: true, .. })) => std::process::exit(0),
+        Ok(response) => {
+            let error = response.and_then(|response| response.error);
+            eprintln!("tiemrzzzz: the running instance refused the launch: {}", error.as_deref().unwrap_or("unexpected reply"));
+            std::process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("tiemrzzzz: couldn't reach the running instance, opening a new window: {err}");
+            None
+        }
+    }
+}
 
 fn main() -> eframe::Result<()> {
+    let args = Args::parse();
+    match &args.command {
+        Some(Action::Ctl { command }) => match control::run_client(command) {
+            Ok(true) => return Ok(()),
+            Ok(false) => std::process::exit(1),
+            Err(err) => {
+                eprintln!("tiemrzzzz: couldn't reach a running timer: {err}");
+                std::process::exit(2);
+            }
+        },
+        Some(Action::Bar { format, output }) => {
+            // Runs until the bar closes the pipe
+            let err = bar::run(*format, output.as_deref()).unwrap_err();
+            if err.kind() != std::io::ErrorKind::BrokenPipe {
+                eprintln!("tiemrzzzz: {err}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Action::Alarm { tune, volume, output }) => {
+            let result = match output {
+                Some(path) => std::fs::write(path, tune.wav(*volume)),
+                None => alarm::preview(*tune, *volume),
+            };
+            if let Err(err) = result {
+                eprintln!("tiemrzzzz: {err}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        None => {}
+    }
+    if args.tui {
+        if let Err(err) = tui::run(&args) {
+            eprintln!("tiemrzzzz: {err}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Held until the window closes
+    let _instance_lock = if args.single_instance { claim_instance(&args) } else { None };
+
+    // Read before the window opens so the overlay starts at its configured size
+    let mut config_file = Config::default_path().map(ConfigFile::new);
+    let config = config_file.as_mut().map_or_else(|| Ok(Config::default()), ConfigFile::load);
+    let overlay_size = config.as_ref().map_or_else(|_| Overlay::default().size(), |config| config.overlay.size());
+
+    // Timers and window from the last run
+    let session = Session::default_path().and_then(|path| {
+        Session::load(&path)
+            .map_err(|err| eprintln!("tiemrzzzz: couldn't restore {}: {err}", path.display()))
+            .ok()
+            .flatten()
+    });
+    let window = session.as_ref().map(|session| session.window).unwrap_or_default();
+
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_inner_size(window.size.unwrap_or([400.0, 400.0]))
+        .with_min_inner_size([180.0, 80.0])
+        .with_title(args.title.as_deref().unwrap_or("Timer"))
+        .with_resizable(true)
+        .with_always_on_top()
+        .with_transparent(true); // Enable transparency
+    if args.overlay || window.overlay {
+        viewport = viewport
+            .with_inner_size(overlay_size)
+            .with_min_inner_size(overlay_size)
+            .with_decorations(false);
+    }
+    if let Some(position) = args.position.or(window.position) {
+        viewport = viewport.with_position(position);
+    }
+
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([400.0, 400.0])
-            .with_min_inner_size([180.0, 80.0])
-            .with_title("Timer")
-            .with_resizable(true)
-            .with_always_on_top()
-            .with_transparent(true), // Enable transparency
+        viewport,
         ..Default::default()
     };
 
     eframe::run_native(
         "Timer",
         options,
-        Box::new(|cc| Ok(Box::new(TimerApp::new(cc)))),
+        Box::new(|cc| Ok(Box::new(TimerApp::new(cc, args, config_file, config, session)))),
     )
 }
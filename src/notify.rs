@@ -0,0 +1,455 @@
+use std::collections::HashMap;
+use std::io;
+
+use crate::config::Seconds;
+use crate::timer::TimerEvent;
+use crate::timers::{NamedTimer, TimerList};
+
+/// A button on a notification, or a click on the notification itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Restart,
+    /// Keep going for this many more seconds
+    Snooze(u32),
+    Dismiss,
+    /// Bring the window to the front
+    Show,
+}
+
+impl Action {
+    /// Key sent to the notification server; "default" is the click on the notification
+    fn key(self) -> String {
+        match self {
+            Action::Restart => "restart".to_owned(),
+            Action::Snooze(seconds) => format!("snooze-{seconds}"),
+            Action::Dismiss => "dismiss".to_owned(),
+            Action::Show => "default".to_owned(),
+        }
+    }
+
+    fn label(self) -> String {
+        match self {
+            Action::Restart => "Restart".to_owned(),
+            Action::Snooze(seconds) => format!("Snooze {}", Seconds(seconds).label()),
+            Action::Dismiss => "Dismiss".to_owned(),
+            Action::Show => "Show".to_owned(),
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "restart" => Some(Action::Restart),
+            "dismiss" => Some(Action::Dismiss),
+            "default" => Some(Action::Show),
+            key => key.strip_prefix("snooze-")?.parse().ok().map(Action::Snooze),
+        }
+    }
+}
+
+/// A notification posted for a timer
+#[derive(Debug, Clone, Copy)]
+struct Posted {
+    id: u32,
+    /// Whether it announces the finished message, and goes away with it
+    finished: bool,
+}
+
+/// Desktop notifications for timers, each replacing the timer's previous one
+pub struct Notifier {
+    client: Client,
+    /// Latest notification by timer id
+    posted: HashMap<u64, Posted>,
+    /// What the Snooze button adds, if there is one
+    snooze: Option<u32>,
+}
+
+impl Notifier {
+    /// Connect to the notification server on the session bus; `wake` is called
+    /// when an action is picked so the app can run it
+    pub fn start(wake: impl Fn() + Send + 'static) -> io::Result<Self> {
+        Client::start(wake).map(Self::new)
+    }
+
+    /// Connect to the notification server on the bus at `address`
+    #[cfg(all(test, target_os = "linux"))]
+    pub fn start_at(address: &str, wake: impl Fn() + Send + 'static) -> io::Result<Self> {
+        Client::start_at(address, wake).map(Self::new)
+    }
+
+    fn new(client: Client) -> Self {
+        Self {
+            client,
+            posted: HashMap::new(),
+            snooze: None,
+        }
+    }
+
+    /// Offer Snooze for `seconds` on finished notifications posted from now on
+    pub fn set_snooze(&mut self, seconds: Option<u32>) {
+        self.snooze = seconds;
+    }
+
+    fn post(&mut self, entry: &NamedTimer, summary: &str, body: &str, actions: &[Action], finished: bool) -> io::Result<()> {
+        let replaces = self.posted.get(&entry.id).map_or(0, |posted| posted.id);
+        let id = self.client.notify(replaces, summary, body, actions, finished)?;
+        self.posted.insert(entry.id, Posted { id, finished });
+        Ok(())
+    }
+
+    /// Announce what `event` means for the timer, if anything
+    pub fn announce(&mut self, entry: &NamedTimer, event: TimerEvent) -> io::Result<()> {
+        match event {
+            TimerEvent::Finished => self.finished(entry),
+            TimerEvent::PhaseChanged => self.phase_changed(entry),
+            _ => Ok(()),
+        }
+    }
+
+    /// Announce the timer's finished message, with buttons to act on it
+    fn finished(&mut self, entry: &NamedTimer) -> io::Result<()> {
+        let Some(notice) = &entry.finished_notice else {
+            return Ok(());
+        };
+        let actions = match (entry.can_snooze(), self.snooze) {
+            (true, Some(seconds)) => vec![Action::Restart, Action::Snooze(seconds), Action::Dismiss, Action::Show],
+            (true, None) => vec![Action::Restart, Action::Dismiss, Action::Show],
+            (false, _) => vec![Action::Dismiss, Action::Show],
+        };
+        self.post(entry, &format!("{} complete", entry.name), notice, &actions, true)
+    }
+
+    /// Announce the phase the timer moved to
+    fn phase_changed(&mut self, entry: &NamedTimer) -> io::Result<()> {
+        // A phase that changed because the timer finished is in the finished notification
+        if entry.finished_notice.is_some() {
+            return Ok(());
+        }
+        let Some(phase) = entry.phase_label() else {
+            return Ok(());
+        };
+        self.post(entry, &entry.name, &phase, &[Action::Show], false)
+    }
+
+    /// Withdraw finished notifications whose message was dismissed in the window
+    pub fn close_dismissed(&mut self, timers: &TimerList) {
+        let dismissed: Vec<u64> = self
+            .posted
+            .iter()
+            .filter(|(id, posted)| posted.finished && !timers.iter().any(|entry| entry.id == **id && entry.finished_notice.is_some()))
+            .map(|(&id, _)| id)
+            .collect();
+        for timer in dismissed {
+            if let Some(posted) = self.posted.remove(&timer) {
+                // Fails harmlessly if the notification was already closed
+                let _ = self.client.close(posted.id);
+            }
+        }
+    }
+
+    /// Actions picked since the last call, as (timer id, action)
+    pub fn actions(&mut self) -> Vec<(u64, Action)> {
+        self.client
+            .actions()
+            .into_iter()
+            .filter_map(|(id, action)| {
+                let timer = self.posted.iter().find(|(_, posted)| posted.id == id)?.0;
+                Some((*timer, action))
+            })
+            .collect()
+    }
+}
+
+#[cfg(target_os = "linux")]
+use linux::Client;
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::collections::HashMap;
+    use std::io;
+    use std::sync::mpsc::{self, Receiver};
+    use std::thread;
+
+    use zbus::blocking::connection;
+    use zbus::proxy;
+    use zbus::zvariant::Value;
+
+    use super::Action;
+
+    #[proxy(
+        interface = "org.freedesktop.Notifications",
+        default_service = "org.freedesktop.Notifications",
+        default_path = "/org/freedesktop/Notifications"
+    )]
+    trait Notifications {
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &self,
+            app_name: &str,
+            replaces_id: u32,
+            app_icon: &str,
+            summary: &str,
+            body: &str,
+            actions: &[&str],
+            hints: HashMap<&str, &Value<'_>>,
+            expire_timeout: i32,
+        ) -> zbus::Result<u32>;
+
+        fn close_notification(&self, id: u32) -> zbus::Result<()>;
+
+        #[zbus(signal)]
+        fn action_invoked(&self, id: u32, action_key: String) -> zbus::Result<()>;
+    }
+
+    /// Talks to `org.freedesktop.Notifications`
+    pub struct Client {
+        proxy: NotificationsProxyBlocking<'static>,
+        /// Picked actions by notification id, from the listener thread
+        actions: Receiver<(u32, Action)>,
+    }
+
+    impl Client {
+        pub fn start(wake: impl Fn() + Send + 'static) -> io::Result<Self> {
+            Self::connect(connection::Builder::session(), wake)
+        }
+
+        #[cfg(test)]
+        pub fn start_at(address: &str, wake: impl Fn() + Send + 'static) -> io::Result<Self> {
+            Self::connect(connection::Builder::address(address), wake)
+        }
+
+        fn connect(builder: zbus::Result<connection::Builder<'_>>, wake: impl Fn() + Send + 'static) -> io::Result<Self> {
+            let connection = builder.and_then(|builder| builder.build()).map_err(io::Error::other)?;
+            let proxy = NotificationsProxyBlocking::new(&connection).map_err(io::Error::other)?;
+            // Subscribe before returning so no early click is missed
+            let invoked = proxy.receive_action_invoked().map_err(io::Error::other)?;
+
+            let (sender, actions) = mpsc::channel();
+            thread::spawn(move || {
+                for signal in invoked {
+                    let Ok(args) = signal.args() else {
+                        continue;
+                    };
+                    if let Some(action) = Action::from_key(args.action_key()) {
+                        if sender.send((*args.id(), action)).is_err() {
+                            break;
+                        }
+                        wake();
+                    }
+                }
+            });
+            Ok(Self { proxy, actions })
+        }
+
+        /// Post a notification, replacing `replaces` unless it is 0; returns its id
+        pub fn notify(&self, replaces: u32, summary: &str, body: &str, actions: &[Action], urgent: bool) -> io::Result<u32> {
+            let actions: Vec<String> = actions.iter().flat_map(|action| [action.key(), action.label()]).collect();
+            let actions: Vec<&str> = actions.iter().map(String::as_str).collect();
+            // Critical notifications stay up until acted on
+            let urgency = Value::U8(if urgent { 2 } else { 1 });
+            let hints = HashMap::from([("urgency", &urgency)]);
+            self.proxy
+                .notify("tiemrzzzz", replaces, "alarm-symbolic", summary, body, &actions, hints, -1)
+                .map_err(io::Error::other)
+        }
+
+        pub fn close(&self, id: u32) -> io::Result<()> {
+            self.proxy.close_notification(id).map_err(io::Error::other)
+        }
+
+        pub fn actions(&self) -> Vec<(u32, Action)> {
+            self.actions.try_iter().collect()
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+use fallback::Client;
+
+#[cfg(not(target_os = "linux"))]
+mod fallback {
+    use std::io;
+
+    use super::Action;
+
+    /// Stand-in where there is no notification server to talk to
+    pub struct Client;
+
+    impl Client {
+        pub fn start(_wake: impl Fn() + Send + 'static) -> io::Result<Self> {
+            Err(io::Error::new(io::ErrorKind::Unsupported, "desktop notifications are only available on Linux"))
+        }
+
+        pub fn notify(&self, _replaces: u32, _summary: &str, _body: &str, _actions: &[Action], _urgent: bool) -> io::Result<u32> {
+            Ok(0)
+        }
+
+        pub fn close(&self, _id: u32) -> io::Result<()> {
+            Ok(())
+        }
+
+        pub fn actions(&self) -> Vec<(u32, Action)> {
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::time::Duration;
+
+    use zbus::blocking::{connection, Connection};
+    use zbus::interface;
+    use zbus::zvariant::OwnedValue;
+
+    use super::*;
+    use crate::dbus::test_bus::Daemon;
+    use crate::pomodoro::PomodoroConfig;
+    use crate::sequence::SequenceConfig;
+    use crate::timers::Mode;
+
+    /// What the stand-in server was asked to do
+    #[derive(Default)]
+    struct Log {
+        /// (id, summary, body, actions, urgency)
+        posted: Vec<(u32, String, String, Vec<String>, u8)>,
+        closed: Vec<u32>,
+    }
+
+    /// Stand-in for a notification daemon
+    struct FakeServer {
+        log: Arc<Mutex<Log>>,
+        next_id: AtomicU32,
+    }
+
+    #[interface(name = "org.freedesktop.Notifications")]
+    impl FakeServer {
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &self,
+            _app_name: String,
+            replaces_id: u32,
+            _app_icon: String,
+            summary: String,
+            body: String,
+            actions: Vec<String>,
+            hints: HashMap<String, OwnedValue>,
+            _expire_timeout: i32,
+        ) -> u32 {
+            let id = match replaces_id {
+                0 => self.next_id.fetch_add(1, Ordering::Relaxed),
+                id => id,
+            };
+            let urgency = hints.get("urgency").and_then(|value| u8::try_from(value).ok()).unwrap_or(1);
+            self.log.lock().unwrap().posted.push((id, summary, body, actions, urgency));
+            id
+        }
+
+        fn close_notification(&self, id: u32) {
+            self.log.lock().unwrap().closed.push(id);
+        }
+    }
+
+    /// Put a stand-in notification daemon on the bus at `address`
+    fn serve(address: &str, log: &Arc<Mutex<Log>>) -> Connection {
+        let server = FakeServer { log: log.clone(), next_id: AtomicU32::new(1) };
+        connection::Builder::address(address)
+            .and_then(|builder| builder.name("org.freedesktop.Notifications"))
+            .and_then(|builder| builder.serve_at("/org/freedesktop/Notifications", server))
+            .and_then(|builder| builder.build())
+            .unwrap()
+    }
+
+    #[test]
+    fn posts_notifications_and_reports_actions() {
+        let Some((_daemon, address)) = Daemon::spawn() else {
+            eprintln!("dbus-daemon not found, skipping");
+            return;
+        };
+        let log = Arc::new(Mutex::new(Log::default()));
+        let server = serve(&address, &log);
+
+        let (woken, wakes) = mpsc::channel();
+        let mut notifier = Notifier::start_at(&address, move || woken.send(()).unwrap()).unwrap();
+        notifier.set_snooze(Some(10 * 60));
+        let mut timers = TimerList::default();
+        timers.add();
+        timers.rename(1, "Tea");
+        timers.primary_mut().finished_notice = Some("⏰ Time's up!".to_owned());
+        notifier.announce(timers.primary(), TimerEvent::Finished).unwrap();
+        {
+            let log = log.lock().unwrap();
+            let (id, summary, body, actions, urgency) = &log.posted[0];
+            assert_eq!((*id, summary.as_str(), body.as_str(), *urgency), (1, "Tea complete", "⏰ Time's up!", 2));
+            assert_eq!(&actions[..4], ["restart", "Restart", "snooze-600", "Snooze 10 min"]);
+        }
+
+        // Another server's notification, then ours
+        server
+            .emit_signal(None::<&str>, "/org/freedesktop/Notifications", "org.freedesktop.Notifications", "ActionInvoked", &(99u32, "snooze-600"))
+            .unwrap();
+        server
+            .emit_signal(None::<&str>, "/org/freedesktop/Notifications", "org.freedesktop.Notifications", "ActionInvoked", &(1u32, "snooze-600"))
+            .unwrap();
+        wakes.recv_timeout(Duration::from_secs(5)).unwrap();
+        wakes.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(notifier.actions(), [(2, Action::Snooze(600))]);
+
+        notifier.close_dismissed(&timers);
+        assert!(log.lock().unwrap().closed.is_empty());
+        timers.primary_mut().dismiss();
+        notifier.close_dismissed(&timers);
+        assert_eq!(log.lock().unwrap().closed, [1]);
+
+        timers.primary_mut().set_mode(Mode::Pomodoro, &PomodoroConfig::default(), &SequenceConfig::default());
+        notifier.announce(timers.primary(), TimerEvent::PhaseChanged).unwrap();
+        notifier.close_dismissed(&timers);
+        let log = log.lock().unwrap();
+        let (_, summary, body, actions, urgency) = log.posted.last().unwrap();
+        assert_eq!((summary.as_str(), body.as_str(), *urgency), ("Tea", "Work 1/4", 1));
+        assert_eq!(actions, &["default", "Show"]);
+        assert_eq!(log.closed, [1], "phase notifications stay up");
+    }
+
+    #[test]
+    fn announces_interval_segments_but_not_phases_that_finished() {
+        let Some((_daemon, address)) = Daemon::spawn() else {
+            eprintln!("dbus-daemon not found, skipping");
+            return;
+        };
+        let log = Arc::new(Mutex::new(Log::default()));
+        let _server = serve(&address, &log);
+        let mut notifier = Notifier::start_at(&address, || ()).unwrap();
+
+        let mut entry = NamedTimer::new("Workout");
+        entry.set_mode(Mode::Intervals, &PomodoroConfig::default(), &SequenceConfig::default());
+        entry.timer.start();
+        entry.timer.extend(-60);
+        entry.drain_events();
+        entry.update();
+        for event in entry.drain_events() {
+            notifier.announce(&entry, event).unwrap();
+        }
+        {
+            let log = log.lock().unwrap();
+            let bodies: Vec<&str> = log.posted.iter().map(|(_, _, body, _, _)| body.as_str()).collect();
+            assert_eq!(bodies, ["Work 1/8"]);
+        }
+
+        entry.set_mode(Mode::Pomodoro, &PomodoroConfig::default(), &SequenceConfig::default());
+        entry.timer.start();
+        entry.timer.extend(-25 * 60);
+        entry.drain_events();
+        entry.update();
+        let events = entry.drain_events();
+        assert!(events.contains(&TimerEvent::PhaseChanged));
+        for event in events {
+            notifier.announce(&entry, event).unwrap();
+        }
+        let log = log.lock().unwrap();
+        let bodies: Vec<&str> = log.posted.iter().map(|(_, _, body, _, _)| body.as_str()).collect();
+        assert_eq!(bodies, ["Work 1/8", "🍅 Work done! Next: Short break"]);
+    }
+}
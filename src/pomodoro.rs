@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+
+use crate::timer::{Clock, Timer, TimerKind};
+
+/// A phase of the Pomodoro cycle
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    pub fn label(self) -> &'static str {
+        match self {
+            Phase::Work => "Work",
+            Phase::ShortBreak => "Short break",
+            Phase::LongBreak => "Long break",
+        }
+    }
+}
+
+/// Phase lengths (in seconds) and cycle behavior
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PomodoroConfig {
+    pub work: u32,
+    pub short_break: u32,
+    pub long_break: u32,
+    /// Take a long break after this many work sessions
+    pub long_break_every: u32,
+    /// Start the next phase automatically when one finishes
+    pub auto_advance: bool,
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        Self {
+            work: 25 * 60,
+            short_break: 5 * 60,
+            long_break: 15 * 60,
+            long_break_every: 4,
+            auto_advance: true,
+        }
+    }
+}
+
+/// Pomodoro cycle driving a countdown [`Timer`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pomodoro {
+    pub config: PomodoroConfig,
+    phase: Phase,
+    /// Work sessions finished so far
+    completed: u32,
+}
+
+impl Pomodoro {
+    pub fn new(config: PomodoroConfig) -> Self {
+        Self {
+            config,
+            phase: Phase::Work,
+            completed: 0,
+        }
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Position of the current (or just finished) work session within its set, 1-based
+    pub fn cycle(&self) -> u32 {
+        let every = self.config.long_break_every.max(1);
+        match self.phase {
+            Phase::Work => self.completed % every + 1,
+            // A break always follows a work session, unless a session file says otherwise
+            Phase::ShortBreak | Phase::LongBreak => self.completed.saturating_sub(1) % every + 1,
+        }
+    }
+
+    pub fn phase_seconds(&self, phase: Phase) -> u32 {
+        match phase {
+            Phase::Work => self.config.work,
+            Phase::ShortBreak => self.config.short_break,
+            Phase::LongBreak => self.config.long_break,
+        }
+    }
+
+    /// The phase that follows the current one
+    pub fn next_phase(&self) -> Phase {
+        match self.phase {
+            Phase::Work if (self.completed + 1).is_multiple_of(self.config.long_break_every.max(1)) => Phase::LongBreak,
+            Phase::Work => Phase::ShortBreak,
+            Phase::ShortBreak | Phase::LongBreak => Phase::Work,
+        }
+    }
+
+    /// Load the current phase into the timer, stopped
+    pub fn apply<C: Clock>(&self, timer: &mut Timer<C>) {
+        timer.kind = TimerKind::Countdown;
+        timer.overtime = false;
+        timer.reset();
+        timer.set_duration(self.phase_seconds(self.phase));
+    }
+
+    /// Move to the next phase, starting it when auto-advance is on
+    pub fn advance<C: Clock>(&mut self, timer: &mut Timer<C>) -> Phase {
+        let next = self.next_phase();
+        if self.phase == Phase::Work {
+            self.completed += 1;
+        }
+        self.phase = next;
+
+        self.apply(timer);
+        if self.config.auto_advance {
+            timer.start();
+        }
+        next
+    }
+
+    /// Label for the display, e.g. "Work 2/4"
+    pub fn status(&self) -> String {
+        format!("{} {}/{}", self.phase.label(), self.cycle(), self.config.long_break_every.max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::{ManualClock, TimerState};
+
+    fn config(auto_advance: bool) -> PomodoroConfig {
+        PomodoroConfig {
+            work: 60,
+            short_break: 10,
+            long_break: 30,
+            long_break_every: 3,
+            auto_advance,
+        }
+    }
+
+    fn setup(auto_advance: bool) -> (Pomodoro, Timer<ManualClock>, ManualClock) {
+        let clock = ManualClock::new();
+        let mut timer = Timer::with_clock(120, clock.clone());
+        let pomodoro = Pomodoro::new(config(auto_advance));
+        pomodoro.apply(&mut timer);
+        (pomodoro, timer, clock)
+    }
+
+    #[test]
+    fn starts_with_work_phase() {
+        let (pomodoro, timer, _) = setup(true);
+        assert_eq!(pomodoro.phase(), Phase::Work);
+        assert_eq!(pomodoro.cycle(), 1);
+        assert_eq!(timer.duration, 60);
+        assert_eq!(timer.state, TimerState::Stopped);
+    }
+
+    #[test]
+    fn long_break_every_n_cycles() {
+        let (mut pomodoro, mut timer, _) = setup(true);
+        let phases: Vec<_> = (0..8).map(|_| pomodoro.advance(&mut timer)).collect();
+        assert_eq!(
+            phases,
+            [
+                Phase::ShortBreak,
+                Phase::Work,
+                Phase::ShortBreak,
+                Phase::Work,
+                Phase::LongBreak,
+                Phase::Work,
+                Phase::ShortBreak,
+                Phase::Work,
+            ]
+        );
+        assert_eq!(pomodoro.completed, 4);
+    }
+
+    #[test]
+    fn cycle_counter_wraps_after_long_break() {
+        let (mut pomodoro, mut timer, _) = setup(true);
+        let mut cycles = vec![pomodoro.cycle()];
+        for _ in 0..7 {
+            pomodoro.advance(&mut timer);
+            cycles.push(pomodoro.cycle());
+        }
+        assert_eq!(cycles, [1, 1, 2, 2, 3, 3, 1, 1]);
+    }
+
+    #[test]
+    fn break_before_any_work_counts_as_the_first_cycle() {
+        let (mut pomodoro, _, _) = setup(true);
+        pomodoro.phase = Phase::ShortBreak;
+        assert_eq!(pomodoro.cycle(), 1);
+        assert_eq!(pomodoro.status(), "Short break 1/3");
+    }
+
+    #[test]
+    fn advance_loads_phase_duration() {
+        let (mut pomodoro, mut timer, _) = setup(true);
+        pomodoro.advance(&mut timer);
+        assert_eq!(timer.duration, 10);
+        assert_eq!(timer.remaining().as_secs(), 10);
+    }
+
+    #[test]
+    fn auto_advance_starts_next_phase() {
+        let (mut pomodoro, mut timer, clock) = setup(true);
+        timer.start();
+        clock.advance_secs(60.0);
+        assert!(timer.tick());
+        pomodoro.advance(&mut timer);
+        assert!(timer.is_running());
+    }
+
+    #[test]
+    fn manual_advance_waits_for_start() {
+        let (mut pomodoro, mut timer, clock) = setup(false);
+        timer.start();
+        clock.advance_secs(60.0);
+        timer.tick();
+        pomodoro.advance(&mut timer);
+        assert_eq!(timer.state, TimerState::Stopped);
+        assert_eq!(timer.remaining().as_secs(), 10);
+    }
+
+    #[test]
+    fn status_shows_phase_and_cycle() {
+        let (mut pomodoro, mut timer, _) = setup(true);
+        assert_eq!(pomodoro.status(), "Work 1/3");
+        pomodoro.advance(&mut timer);
+        assert_eq!(pomodoro.status(), "Short break 1/3");
+    }
+}
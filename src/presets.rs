@@ -0,0 +1,205 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::timer::MAX_DURATION;
+
+/// A named countdown length, e.g. "Tea 3:30"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub seconds: u32,
+}
+
+impl Preset {
+    pub fn new(name: &str, seconds: u32) -> Self {
+        Self {
+            name: name.trim().to_owned(),
+            seconds: seconds.min(MAX_DURATION),
+        }
+    }
+
+    /// Name and length, e.g. "Tea 3:30" or "Focus 1:30:00"
+    pub fn label(&self) -> String {
+        let (hours, minutes, seconds) = (self.seconds / 3600, (self.seconds / 60) % 60, self.seconds % 60);
+        if hours > 0 {
+            format!("{} {hours}:{minutes:02}:{seconds:02}", self.name)
+        } else {
+            format!("{} {minutes}:{seconds:02}", self.name)
+        }
+    }
+}
+
+/// Saved presets, stored as TOML in the config directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Presets {
+    #[serde(default, rename = "preset")]
+    presets: Vec<Preset>,
+}
+
+impl Default for Presets {
+    fn default() -> Self {
+        Self {
+            presets: vec![
+                Preset::new("Tea", 3 * 60 + 30),
+                Preset::new("Break", 5 * 60),
+                Preset::new("Standup", 15 * 60),
+            ],
+        }
+    }
+}
+
+impl Presets {
+    /// Default location, e.g. `~/.config/tiemrzzzz/presets.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("tiemrzzzz").join("presets.toml"))
+    }
+
+    /// Read presets from `path`; a missing file gives the built-in defaults
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut presets: Self = toml::from_str(text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        presets.presets.retain(|preset| !preset.name.trim().is_empty());
+        for preset in &mut presets.presets {
+            *preset = Preset::new(&preset.name, preset.seconds);
+        }
+        Ok(presets)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_toml())
+    }
+
+    pub fn as_slice(&self) -> &[Preset] {
+        &self.presets
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Preset> {
+        self.presets.get(index)
+    }
+
+    /// Find a preset by name, ignoring case
+    pub fn find(&self, name: &str) -> Option<&Preset> {
+        self.presets.iter().find(|p| p.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Add a preset, replacing any existing one with the same name
+    pub fn add(&mut self, preset: Preset) {
+        if preset.name.is_empty() {
+            return;
+        }
+        match self.presets.iter_mut().find(|p| p.name.eq_ignore_ascii_case(&preset.name)) {
+            Some(existing) => *existing = preset,
+            None => self.presets.push(preset),
+        }
+    }
+
+    /// Replace the preset at `index`; blank names are ignored
+    pub fn update(&mut self, index: usize, preset: Preset) {
+        if let (Some(slot), false) = (self.presets.get_mut(index), preset.name.is_empty()) {
+            *slot = preset;
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.presets.len() {
+            self.presets.remove(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_show_length() {
+        assert_eq!(Preset::new("Tea", 210).label(), "Tea 3:30");
+        assert_eq!(Preset::new("Focus", 5400).label(), "Focus 1:30:00");
+    }
+
+    #[test]
+    fn round_trips_through_toml() {
+        let presets = Presets::default();
+        let text = presets.to_toml();
+        assert!(text.contains("[[preset]]"));
+        assert_eq!(Presets::parse(&text).unwrap(), presets);
+    }
+
+    #[test]
+    fn parse_cleans_up_entries() {
+        let presets = Presets::parse(
+            "[[preset]]\nname = \" Tea \"\nseconds = 210\n\n\
+             [[preset]]\nname = \"\"\nseconds = 60\n\n\
+             [[preset]]\nname = \"Long\"\nseconds = 999999\n",
+        )
+        .unwrap();
+        assert_eq!(presets.as_slice(), [Preset::new("Tea", 210), Preset::new("Long", MAX_DURATION)]);
+    }
+
+    #[test]
+    fn parse_rejects_invalid_toml() {
+        let err = Presets::parse("[[preset]\n").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn empty_file_has_no_presets() {
+        assert!(Presets::parse("").unwrap().as_slice().is_empty());
+    }
+
+    #[test]
+    fn add_replaces_same_name() {
+        let mut presets = Presets::default();
+        presets.add(Preset::new("tea", 240));
+        presets.add(Preset::new("Nap", 1200));
+        presets.add(Preset::new("  ", 60));
+        let labels: Vec<_> = presets.as_slice().iter().map(Preset::label).collect();
+        assert_eq!(labels, ["tea 4:00", "Break 5:00", "Standup 15:00", "Nap 20:00"]);
+    }
+
+    #[test]
+    fn find_ignores_case() {
+        let presets = Presets::default();
+        assert_eq!(presets.find(" standup"), Some(&Preset::new("Standup", 900)));
+        assert_eq!(presets.find("Nap"), None);
+    }
+
+    #[test]
+    fn update_and_remove() {
+        let mut presets = Presets::default();
+        presets.update(0, Preset::new("Green tea", 120));
+        presets.update(1, Preset::new("", 120));
+        presets.remove(2);
+        presets.remove(9);
+        assert_eq!(presets.as_slice(), [Preset::new("Green tea", 120), Preset::new("Break", 300)]);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join(format!("tiemrzzzz-presets-{}", std::process::id())).join("presets.toml");
+        let mut presets = Presets::default();
+        presets.add(Preset::new("Nap", 1200));
+        presets.save(&path).unwrap();
+        assert_eq!(Presets::load(&path).unwrap(), presets);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(Presets::load(&path).unwrap(), Presets::default());
+    }
+}
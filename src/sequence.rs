@@ -0,0 +1,229 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::timer::{Clock, Timer, TimerKind};
+
+/// A named interval, e.g. "Work 0:40"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    pub name: String,
+    pub seconds: u32,
+}
+
+impl Segment {
+    pub fn new(name: &str, seconds: u32) -> Self {
+        Self {
+            name: name.to_owned(),
+            seconds,
+        }
+    }
+
+    /// Name and length, e.g. "Rest 0:20"
+    pub fn describe(&self) -> String {
+        format!("{} {}:{:02}", self.name, self.seconds / 60, self.seconds % 60)
+    }
+}
+
+/// Segments repeated for a number of rounds, with optional warm-up and cool-down
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SequenceConfig {
+    pub warmup: Option<Segment>,
+    pub segments: Vec<Segment>,
+    pub rounds: u32,
+    pub cooldown: Option<Segment>,
+}
+
+impl Default for SequenceConfig {
+    fn default() -> Self {
+        Self {
+            warmup: Some(Segment::new("Warm-up", 60)),
+            segments: vec![Segment::new("Work", 40), Segment::new("Rest", 20)],
+            rounds: 8,
+            cooldown: Some(Segment::new("Cool-down", 60)),
+        }
+    }
+}
+
+/// One entry of the flattened sequence
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Step {
+    pub segment: Segment,
+    /// Round number (1-based); `None` for warm-up and cool-down
+    pub round: Option<u32>,
+}
+
+/// Runs a [`SequenceConfig`] on a countdown [`Timer`], one segment after another
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sequence {
+    pub config: SequenceConfig,
+    steps: Vec<Step>,
+    index: usize,
+}
+
+impl Sequence {
+    pub fn new(config: SequenceConfig) -> Self {
+        let mut steps = Vec::new();
+        if let Some(warmup) = &config.warmup {
+            steps.push(Step { segment: warmup.clone(), round: None });
+        }
+        for round in 1..=config.rounds {
+            for segment in &config.segments {
+                steps.push(Step { segment: segment.clone(), round: Some(round) });
+            }
+        }
+        if let Some(cooldown) = &config.cooldown {
+            steps.push(Step { segment: cooldown.clone(), round: None });
+        }
+        // Zero-length segments would finish instantly
+        steps.retain(|step| step.segment.seconds > 0);
+
+        Self { config, steps, index: 0 }
+    }
+
+    pub fn current(&self) -> Option<&Step> {
+        self.steps.get(self.index)
+    }
+
+    pub fn upcoming(&self) -> Option<&Step> {
+        self.steps.get(self.index + 1)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.index >= self.steps.len()
+    }
+
+    /// Load the current segment into the timer, stopped
+    pub fn apply<C: Clock>(&self, timer: &mut Timer<C>) {
+        timer.kind = TimerKind::Countdown;
+        timer.overtime = false;
+        timer.reset();
+        timer.set_duration(self.current().map_or(0, |step| step.segment.seconds));
+    }
+
+    /// Move to the next segment and start it; returns false once the sequence is over
+    pub fn advance<C: Clock>(&mut self, timer: &mut Timer<C>) -> bool {
+        if self.is_done() {
+            return false;
+        }
+        self.index += 1;
+        if self.is_done() {
+            return false;
+        }
+        self.apply(timer);
+        timer.start();
+        true
+    }
+
+    /// Label for the display, e.g. "Work 3/8"
+    pub fn status(&self) -> String {
+        match self.current() {
+            Some(Step { segment, round: Some(round) }) => format!("{} {}/{}", segment.name, round, self.config.rounds),
+            Some(Step { segment, round: None }) => segment.name.clone(),
+            None => "Done".to_owned(),
+        }
+    }
+
+    /// Preview of the following segment, e.g. "Next: Rest 0:20"
+    pub fn next_up(&self) -> Option<String> {
+        self.upcoming().map(|step| format!("Next: {}", step.segment.describe()))
+    }
+}
+
+/// Whole seconds left when inside the final three seconds of a segment
+pub fn countdown_second(remaining: Duration) -> Option<u64> {
+    let seconds = remaining.as_millis().div_ceil(1000) as u64;
+    (1..=3).contains(&seconds).then_some(seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::{ManualClock, TimerState};
+
+    fn config() -> SequenceConfig {
+        SequenceConfig {
+            warmup: Some(Segment::new("Warm-up", 30)),
+            segments: vec![Segment::new("Work", 20), Segment::new("Rest", 10)],
+            rounds: 2,
+            cooldown: Some(Segment::new("Cool-down", 30)),
+        }
+    }
+
+    fn setup(config: SequenceConfig) -> (Sequence, Timer<ManualClock>, ManualClock) {
+        let clock = ManualClock::new();
+        let mut timer = Timer::with_clock(120, clock.clone());
+        let sequence = Sequence::new(config);
+        sequence.apply(&mut timer);
+        (sequence, timer, clock)
+    }
+
+    fn names(sequence: &Sequence) -> Vec<&str> {
+        sequence.steps.iter().map(|step| step.segment.name.as_str()).collect()
+    }
+
+    #[test]
+    fn flattens_warmup_rounds_and_cooldown() {
+        let (sequence, timer, _) = setup(config());
+        assert_eq!(names(&sequence), ["Warm-up", "Work", "Rest", "Work", "Rest", "Cool-down"]);
+        assert_eq!(timer.duration, 30);
+        assert_eq!(timer.state, TimerState::Stopped);
+    }
+
+    #[test]
+    fn skips_missing_and_empty_segments() {
+        let config = SequenceConfig {
+            warmup: None,
+            cooldown: Some(Segment::new("Cool-down", 0)),
+            ..config()
+        };
+        let (sequence, _, _) = setup(config);
+        assert_eq!(names(&sequence), ["Work", "Rest", "Work", "Rest"]);
+    }
+
+    #[test]
+    fn status_shows_round_progress() {
+        let (mut sequence, mut timer, _) = setup(config());
+        assert_eq!(sequence.status(), "Warm-up");
+        assert_eq!(sequence.next_up().as_deref(), Some("Next: Work 0:20"));
+
+        sequence.advance(&mut timer);
+        sequence.advance(&mut timer);
+        assert_eq!(sequence.status(), "Rest 1/2");
+        assert_eq!(sequence.next_up().as_deref(), Some("Next: Work 0:20"));
+    }
+
+    #[test]
+    fn advancing_starts_the_next_segment() {
+        let (mut sequence, mut timer, clock) = setup(config());
+        timer.start();
+        clock.advance_secs(30.0);
+        assert!(timer.tick());
+        assert!(sequence.advance(&mut timer));
+        assert!(timer.is_running());
+        assert_eq!(timer.duration, 20);
+        assert_eq!(timer.remaining().as_secs(), 20);
+    }
+
+    #[test]
+    fn finishes_after_last_segment() {
+        let (mut sequence, mut timer, _) = setup(config());
+        for _ in 0..5 {
+            assert!(sequence.advance(&mut timer));
+        }
+        assert_eq!(sequence.next_up(), None);
+        assert!(!sequence.advance(&mut timer));
+        assert!(sequence.is_done());
+        assert_eq!(sequence.status(), "Done");
+        assert!(!sequence.advance(&mut timer));
+    }
+
+    #[test]
+    fn countdown_beeps_in_last_three_seconds() {
+        assert_eq!(countdown_second(Duration::from_millis(3_500)), None);
+        assert_eq!(countdown_second(Duration::from_millis(3_000)), Some(3));
+        assert_eq!(countdown_second(Duration::from_millis(2_400)), Some(3));
+        assert_eq!(countdown_second(Duration::from_millis(200)), Some(1));
+        assert_eq!(countdown_second(Duration::ZERO), None);
+    }
+}
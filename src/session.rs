@@ -0,0 +1,263 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::pomodoro::Pomodoro;
+use crate::sequence::Sequence;
+use crate::timer::{Timer, TimerKind, TimerState, MAX_DURATION};
+use crate::timers::{Mode, NamedTimer, TimerDefaults, TimerList};
+
+/// Where the window was and how it was shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct WindowState {
+    /// Top-left corner on screen; unknown on some platforms, e.g. Wayland
+    pub position: Option<[f32; 2]>,
+    /// Inner size of the full UI
+    pub size: Option<[f32; 2]>,
+    pub overlay: bool,
+}
+
+/// A timer as saved in the session file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedTimer {
+    pub name: String,
+    pub mode: Mode,
+    /// Countdown length in seconds
+    pub duration: u32,
+    /// Seconds of `duration` added by extending or snoozing
+    #[serde(default)]
+    pub extended: i32,
+    pub overtime: bool,
+    pub state: TimerState,
+    /// Milliseconds run as of [`Session::saved_at`]
+    pub elapsed: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pomodoro: Option<Pomodoro>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<Sequence>,
+    /// Finished message not yet dismissed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_notice: Option<String>,
+}
+
+impl SavedTimer {
+    pub fn of(entry: &NamedTimer) -> Self {
+        Self {
+            name: entry.name.clone(),
+            mode: entry.mode(),
+            duration: entry.timer.duration,
+            extended: entry.extended,
+            overtime: entry.timer.overtime,
+            state: entry.timer.state,
+            elapsed: entry.timer.elapsed().as_millis() as u64,
+            preset: entry.preset.clone(),
+            pomodoro: entry.pomodoro.clone(),
+            sequence: entry.sequence.clone(),
+            finished_notice: entry.finished_notice.clone(),
+        }
+    }
+
+    /// Rebuild the timer, counting `since` as run if it was running
+    pub fn restore(&self, since: Duration) -> NamedTimer {
+        let mut entry = NamedTimer::new(&self.name);
+        entry.timer = Timer::new(self.duration.min(MAX_DURATION));
+        entry.timer.kind = match self.mode {
+            Mode::Stopwatch => TimerKind::Stopwatch,
+            Mode::Countdown | Mode::Pomodoro | Mode::Intervals => TimerKind::Countdown,
+        };
+        entry.timer.overtime = self.overtime;
+        entry.extended = self.extended.min(entry.timer.duration as i32);
+        entry.timer.restore(self.state, Duration::from_millis(self.elapsed), since);
+        entry.preset = self.preset.clone();
+        entry.pomodoro = self.pomodoro.clone().filter(|_| self.mode == Mode::Pomodoro);
+        entry.sequence = self.sequence.clone().filter(|_| self.mode == Mode::Intervals);
+        entry.finished_notice = self.finished_notice.clone();
+        entry
+    }
+
+    /// Wall-clock time the current run would have started without pauses, for running timers
+    fn anchor(&self, saved_at: u64) -> Option<i64> {
+        (self.state == TimerState::Running).then_some(saved_at as i64 - self.elapsed as i64)
+    }
+}
+
+/// Timers and window saved between runs, so a restart or crash doesn't lose them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// Unix time of the snapshot in milliseconds
+    pub saved_at: u64,
+    pub timers: Vec<SavedTimer>,
+    pub primary: usize,
+    #[serde(default)]
+    pub window: WindowState,
+}
+
+impl Session {
+    /// Default location, e.g. `~/.local/state/tiemrzzzz/session.json`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .map(|dir| dir.join("tiemrzzzz").join("session.json"))
+    }
+
+    /// Read the last session from `path`; `None` when there is none
+    pub fn load(path: &Path) -> io::Result<Option<Self>> {
+        match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map(Some)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Write the session, replacing the old file in one step so a crash never leaves half of it
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let partial = path.with_extension("partial");
+        fs::write(&partial, serde_json::to_string_pretty(self)?)?;
+        fs::rename(partial, path)
+    }
+
+    /// Snapshot the timers as they are now
+    pub fn capture(timers: &TimerList, window: WindowState) -> Self {
+        Self {
+            saved_at: unix_time(),
+            timers: timers.iter().map(SavedTimer::of).collect(),
+            primary: timers.primary_index(),
+            window,
+        }
+    }
+
+    /// The saved timers, with running ones moved on by the time since the snapshot
+    pub fn restore(&self, defaults: TimerDefaults) -> TimerList {
+        let since = Duration::from_millis(unix_time().saturating_sub(self.saved_at));
+        let timers = self.timers.iter().map(|saved| saved.restore(since)).collect();
+        TimerList::from_timers(defaults, timers, self.primary)
+    }
+
+    /// Whether both snapshots hold the same session; running timers only
+    /// differ if they were started, paused or changed in between
+    pub fn matches(&self, other: &Session) -> bool {
+        let same_timer = |a: &SavedTimer, b: &SavedTimer| {
+            let same_run = match (a.anchor(self.saved_at), b.anchor(other.saved_at)) {
+                (Some(a), Some(b)) => (a - b).abs() < 500,
+                _ => a.elapsed == b.elapsed,
+            };
+            same_run && SavedTimer { elapsed: 0, ..a.clone() } == SavedTimer { elapsed: 0, ..b.clone() }
+        };
+        self.primary == other.primary
+            && self.window == other.window
+            && self.timers.len() == other.timers.len()
+            && self.timers.iter().zip(&other.timers).all(|(a, b)| same_timer(a, b))
+    }
+}
+
+/// Milliseconds since the Unix epoch
+fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pomodoro::PomodoroConfig;
+    use crate::sequence::SequenceConfig;
+
+    fn session() -> TimerList {
+        let mut list = TimerList::default();
+        list.primary_mut().set_countdown(600);
+        list.primary_mut().timer.start();
+        list.add();
+        list.primary_mut()
+            .set_mode(Mode::Pomodoro, &PomodoroConfig::default(), &SequenceConfig::default());
+        list.primary_mut().skip_phase();
+        list.primary_mut().timer.pause();
+        list.select(0);
+        list
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let saved = Session::capture(&session(), WindowState { position: Some([10.0, 20.0]), size: None, overlay: true });
+        let text = serde_json::to_string(&saved).unwrap();
+        assert_eq!(serde_json::from_str::<Session>(&text).unwrap(), saved);
+
+        let list = saved.restore(TimerDefaults::default());
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.primary_index(), 0);
+        let focus = list.get(1).unwrap();
+        assert_eq!(focus.mode(), Mode::Pomodoro);
+        assert_eq!(focus.phase_label().as_deref(), Some("Short break 1/4"));
+        assert_eq!(focus.timer.state, TimerState::Paused);
+    }
+
+    #[test]
+    fn running_timers_catch_up() {
+        let mut saved = Session::capture(&session(), WindowState::default());
+        saved.saved_at -= 120_000;
+        let mut list = saved.restore(TimerDefaults::default());
+        assert!(list.primary().timer.is_running());
+        assert!((479..=480).contains(&list.primary().timer.remaining().as_secs()));
+        assert!(list.update_all().is_empty(), "restoring queues no events");
+
+        saved.saved_at -= 600_000;
+        let mut list = saved.restore(TimerDefaults::default());
+        assert!(list.update_all().contains(&(0, crate::timer::TimerEvent::Finished)));
+    }
+
+    #[test]
+    fn finished_timers_come_back_ringing() {
+        let mut list = TimerList::default();
+        list.primary_mut().set_countdown(60);
+        list.primary_mut().timer.start();
+        let mut saved = Session::capture(&list, WindowState::default());
+        saved.saved_at -= 120_000;
+        let mut list = saved.restore(TimerDefaults::default());
+        list.update_all();
+        assert!(list.primary().finished_notice.is_some());
+
+        let saved = Session::capture(&list, WindowState::default());
+        let text = serde_json::to_string(&saved).unwrap();
+        let mut list = serde_json::from_str::<Session>(&text).unwrap().restore(TimerDefaults::default());
+        assert_eq!(list.primary().finished_notice.as_deref(), Some("⏰ Time's up!"));
+        assert!(list.primary().timer.is_finished());
+        assert!(list.update_all().is_empty(), "it doesn't finish a second time");
+    }
+
+    #[test]
+    fn matches_ignores_running_time() {
+        let mut list = session();
+        let first = Session::capture(&list, WindowState::default());
+        let mut later = first.clone();
+        later.saved_at += 3000;
+        later.timers[0].elapsed += 3000;
+        assert!(first.matches(&later));
+
+        later.timers[1].elapsed += 3000;
+        assert!(!first.matches(&later), "paused timers don't move");
+
+        list.primary_mut().timer.pause();
+        assert!(!first.matches(&Session::capture(&list, WindowState::default())));
+    }
+
+    #[test]
+    fn saves_and_loads() {
+        let path = std::env::temp_dir().join(format!("tiemrzzzz-session-{}.json", std::process::id()));
+        assert_eq!(Session::load(&path).unwrap(), None);
+        let saved = Session::capture(&session(), WindowState::default());
+        saved.save(&path).unwrap();
+        assert_eq!(Session::load(&path).unwrap(), Some(saved));
+
+        fs::write(&path, "{").unwrap();
+        assert_eq!(Session::load(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,169 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// Sample rate for synthesized sounds
+pub const SAMPLE_RATE: u32 = 22_050;
+
+/// Synthesize an 8-bit style square wave
+pub fn square_wave(frequency: f32, millis: u32, volume: f32) -> Vec<i16> {
+    let count = (SAMPLE_RATE * millis / 1000) as usize;
+    let period = SAMPLE_RATE as f32 / frequency;
+    let amplitude = (volume.clamp(0.0, 1.0) * i16::MAX as f32) as i16;
+    (0..count)
+        .map(|i| if (i as f32 % period) < period / 2.0 { amplitude } else { -amplitude })
+        .collect()
+}
+
+/// Encode mono 16-bit samples as a WAV file
+pub fn wav_bytes(samples: &[i16]) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes()); // fmt chunk size
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // Mono
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes()); // Byte rate
+    bytes.extend_from_slice(&2u16.to_le_bytes()); // Block align
+    bytes.extend_from_slice(&16u16.to_le_bytes()); // Bits per sample
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+    bytes
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Beep volume as `f32` bits
+static VOLUME: AtomicU32 = AtomicU32::new(0.3f32.to_bits());
+
+/// Turn sounds on or off and set the beep volume (0.0 to 1.0)
+pub fn configure(enabled: bool, volume: f32) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+    VOLUME.store(volume.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn volume() -> f32 {
+    f32::from_bits(VOLUME.load(Ordering::Relaxed))
+}
+
+/// Play a short beep in the background
+pub fn beep(frequency: f32, millis: u32) {
+    if enabled() {
+        play_samples(square_wave(frequency, millis, volume()));
+    }
+}
+
+/// Play synthesized samples in the background
+pub fn play_samples(samples: Vec<i16>) {
+    let path = temp_wav();
+    thread::spawn(move || {
+        if fs::write(&path, wav_bytes(&samples)).is_ok() {
+            let _ = play_file(&path, 1.0, &AtomicBool::new(false));
+            let _ = fs::remove_file(&path);
+        }
+    });
+}
+
+/// A fresh temporary path for synthesized samples
+pub fn temp_wav() -> PathBuf {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    env::temp_dir().join(format!("tiemrzzzz-{}-{n}.wav", std::process::id()))
+}
+
+/// Play a sound file with the first available system player at `volume`
+/// (0.0 to 1.0, where the player supports it), blocking until done or `stop` is set
+pub fn play_file(path: &Path, volume: f32, stop: &AtomicBool) -> io::Result<()> {
+    let mut last_err = io::Error::new(io::ErrorKind::NotFound, "no audio player found");
+    for (program, args) in players(path, volume) {
+        let child = Command::new(program)
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        match child.and_then(|mut child| wait_or_stop(&mut child, stop)) {
+            Ok(None) => return Ok(()),
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => last_err = io::Error::other(format!("{program} exited with {status}")),
+            // Not installed; try the next one
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => last_err = err,
+        }
+    }
+    Err(last_err)
+}
+
+/// Wait for a player to finish; kills it and returns `None` once `stop` is set
+fn wait_or_stop(child: &mut std::process::Child, stop: &AtomicBool) -> io::Result<Option<std::process::ExitStatus>> {
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if stop.load(Ordering::Relaxed) {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+/// Candidate players for the current platform, most capable first
+fn players(path: &Path, volume: f32) -> Vec<(&'static str, Vec<String>)> {
+    let file = path.display().to_string();
+    let volume = volume.clamp(0.0, 1.0);
+    if cfg!(target_os = "windows") {
+        let script = format!("(New-Object Media.SoundPlayer '{}').PlaySync()", file.replace('\'', "''"));
+        vec![("powershell", vec!["-NoProfile".into(), "-Command".into(), script])]
+    } else if cfg!(target_os = "macos") {
+        vec![("afplay", vec!["-v".into(), volume.to_string(), file])]
+    } else {
+        vec![
+            ("pw-play", vec![format!("--volume={volume}"), file.clone()]),
+            ("paplay", vec![format!("--volume={}", (volume * 65536.0) as u32), file.clone()]),
+            // No volume control; fine for synthesized sounds, which have it baked in
+            ("aplay", vec!["-q".into(), file]),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn square_wave_has_expected_length_and_amplitude() {
+        let samples = square_wave(440.0, 100, 0.5);
+        assert_eq!(samples.len(), SAMPLE_RATE as usize / 10);
+        let peak = (0.5 * i16::MAX as f32) as i16;
+        assert!(samples.iter().all(|&s| s == peak || s == -peak));
+        assert!(samples.contains(&peak) && samples.contains(&-peak));
+    }
+
+    #[test]
+    fn wav_header_describes_samples() {
+        let bytes = wav_bytes(&[0, 1, -1, i16::MAX]);
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(u32::from_le_bytes(bytes[24..28].try_into().unwrap()), SAMPLE_RATE);
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(u32::from_le_bytes(bytes[40..44].try_into().unwrap()), 8);
+        assert_eq!(bytes.len(), 44 + 8);
+    }
+}
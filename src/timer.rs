@@ -1,55 +1,176 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+/// Longest settable duration (99:59:59)
+pub const MAX_DURATION: u32 = 99 * 3600 + 59 * 60 + 59;
+
+/// Source of the current time for a [`Timer`]
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+/// Clock backed by the operating system's monotonic clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Clock that only moves when a test advances it
+#[cfg(test)]
+#[derive(Clone)]
+pub struct ManualClock {
+    base: Instant,
+    offset: std::rc::Rc<std::cell::Cell<Duration>>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn new() -> Self {
+        Self {
+            base: Instant::now(),
+            offset: Default::default(),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.offset.set(self.offset.get() + by);
+    }
+
+    pub fn advance_secs(&self, secs: f32) {
+        self.advance(Duration::from_secs_f32(secs));
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.base + self.offset.get()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TimerState {
     Stopped,
     Running,
     Paused,
 }
 
-pub struct Timer {
+/// A transition of a timer, queued until drained with [`Timer::drain_events`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerEvent {
+    Started,
+    Paused,
+    Resumed,
+    /// The countdown reached zero
+    Finished,
+    Reset,
+    DurationChanged,
+    /// A pomodoro phase or interval segment changed; queued by `NamedTimer`, not `Timer`
+    PhaseChanged,
+    /// An interval segment ran out; queued by `NamedTimer`
+    SegmentEnded,
+    /// One of the last three seconds of an interval segment began; queued by `NamedTimer`
+    CountdownSecond,
+}
+
+impl TimerEvent {
+    /// Name for scripts, e.g. "duration_changed"
+    pub fn name(self) -> &'static str {
+        match self {
+            TimerEvent::Started => "started",
+            TimerEvent::Paused => "paused",
+            TimerEvent::Resumed => "resumed",
+            TimerEvent::Finished => "finished",
+            TimerEvent::Reset => "reset",
+            TimerEvent::DurationChanged => "duration_changed",
+            TimerEvent::PhaseChanged => "phase_changed",
+            TimerEvent::SegmentEnded => "segment_ended",
+            TimerEvent::CountdownSecond => "countdown_second",
+        }
+    }
+}
+
+/// Whether the timer counts down from its duration or up from zero
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimerKind {
+    Countdown,
+    Stopwatch,
+}
+
+pub struct Timer<C: Clock = SystemClock> {
+    /// Counting direction
+    pub kind: TimerKind,
     /// Total duration in seconds
     pub duration: u32,
-    /// Remaining time in seconds
-    pub remaining: f32,
     /// Current state
     pub state: TimerState,
-    /// Last tick time
-    last_tick: Option<Instant>,
+    /// Keep counting past zero instead of stopping
+    pub overtime: bool,
+    /// Whether this run has already reached zero
+    zero_reached: bool,
+    /// When the current running stretch began
+    started_at: Option<Instant>,
+    /// Time elapsed in earlier running stretches
+    accumulated: Duration,
+    /// Transitions not yet drained
+    events: Vec<TimerEvent>,
+    /// Time source
+    clock: C,
 }
 
 impl Default for Timer {
     fn default() -> Self {
-        Self {
-            duration: 120, // Default 2 minutes
-            remaining: 120.0,
-            state: TimerState::Stopped,
-            last_tick: None,
-        }
+        Self::new(120) // Default 2 minutes
     }
 }
 
 impl Timer {
     pub fn new(duration_seconds: u32) -> Self {
+        Self::with_clock(duration_seconds, SystemClock)
+    }
+}
+
+impl<C: Clock> Timer<C> {
+    pub fn with_clock(duration_seconds: u32, clock: C) -> Self {
         Self {
+            kind: TimerKind::Countdown,
             duration: duration_seconds,
-            remaining: duration_seconds as f32,
             state: TimerState::Stopped,
-            last_tick: None,
+            overtime: false,
+            zero_reached: false,
+            started_at: None,
+            accumulated: Duration::ZERO,
+            events: Vec::new(),
+            clock,
         }
     }
 
     pub fn start(&mut self) {
-        if self.remaining > 0.0 {
+        let can_run = self.kind == TimerKind::Stopwatch
+            || !self.remaining().is_zero()
+            || (self.overtime && self.state == TimerState::Paused);
+        if self.state != TimerState::Running && can_run {
+            self.events.push(match self.state {
+                TimerState::Paused => TimerEvent::Resumed,
+                _ => TimerEvent::Started,
+            });
             self.state = TimerState::Running;
-            self.last_tick = Some(Instant::now());
+            self.started_at = Some(self.clock.now());
         }
     }
 
     pub fn pause(&mut self) {
         if self.state == TimerState::Running {
+            self.accumulated = self.elapsed();
             self.state = TimerState::Paused;
-            self.last_tick = None;
+            self.started_at = None;
+            self.events.push(TimerEvent::Paused);
         }
     }
 
@@ -62,60 +183,171 @@ impl Timer {
     }
 
     pub fn reset(&mut self) {
-        self.remaining = self.duration as f32;
+        // Only a timer that has been used reports a reset
+        if self.state != TimerState::Stopped || !self.accumulated.is_zero() || self.zero_reached {
+            self.events.push(TimerEvent::Reset);
+        }
+        self.accumulated = Duration::ZERO;
+        self.zero_reached = false;
         self.state = TimerState::Stopped;
-        self.last_tick = None;
+        self.started_at = None;
+    }
+
+    /// Switch between countdown and stopwatch, resetting the timer
+    pub fn set_kind(&mut self, kind: TimerKind) {
+        self.kind = kind;
+        self.reset();
     }
 
+    /// Change the duration; a running or paused countdown keeps its remaining time
     pub fn set_duration(&mut self, seconds: u32) {
-        self.duration = seconds;
+        if seconds != self.duration {
+            self.events.push(TimerEvent::DurationChanged);
+        }
         if self.state == TimerState::Stopped {
-            self.remaining = seconds as f32;
+            // A finished countdown starts over, so reaching zero again finishes it
+            self.duration = seconds;
+            self.accumulated = Duration::ZERO;
+            self.zero_reached = false;
+            return;
         }
+
+        let remaining = self.remaining();
+        let over = self.overtime_elapsed();
+        if let Some(started) = self.started_at.as_mut() {
+            // Fold the current stretch in so the new elapsed time is measured from now
+            *started = self.clock.now();
+        }
+        self.duration = seconds;
+        self.accumulated = self.total().saturating_sub(remaining) + over;
+        if self.elapsed() < self.total() {
+            self.zero_reached = false;
+        }
+    }
+
+    pub fn add_hours(&mut self, hours: i32) {
+        self.add_seconds(hours * 3600);
     }
 
     pub fn add_minutes(&mut self, minutes: i32) {
-        let new_duration = (self.duration as i32 + minutes * 60).max(0).min(5999) as u32;
-        self.duration = new_duration;
-        if self.state == TimerState::Stopped {
-            self.remaining = new_duration as f32;
-        }
+        self.add_seconds(minutes * 60);
     }
 
     pub fn add_seconds(&mut self, seconds: i32) {
-        let new_duration = (self.duration as i32 + seconds).max(0).min(5999) as u32;
-        self.duration = new_duration;
-        if self.state == TimerState::Stopped {
-            self.remaining = new_duration as f32;
+        let new_duration = (self.duration as i64 + seconds as i64).clamp(0, MAX_DURATION as i64) as u32;
+        self.set_duration(new_duration);
+    }
+
+    /// Add time to (or take it from) what is left of the countdown, keeping the time already run
+    pub fn extend(&mut self, seconds: i32) {
+        let duration = (self.duration as i64 + seconds as i64).clamp(0, MAX_DURATION as i64) as u32;
+        if duration != self.duration {
+            self.events.push(TimerEvent::DurationChanged);
+        }
+        self.duration = duration;
+        if self.elapsed() < self.total() {
+            // Back above zero, so reaching it again counts as finishing
+            self.zero_reached = false;
         }
     }
 
-    /// Update the timer - call this every frame
-    pub fn tick(&mut self) {
-        if self.state != TimerState::Running {
-            return;
+    /// Put the timer back as it was saved `since` ago; a running timer counts that
+    /// time as run, so a countdown that ran out meanwhile finishes on the next tick
+    pub fn restore(&mut self, state: TimerState, elapsed: Duration, since: Duration) {
+        self.zero_reached = self.kind == TimerKind::Countdown && elapsed >= self.total();
+        self.state = state;
+        self.accumulated = match state {
+            TimerState::Running => elapsed + since,
+            TimerState::Stopped | TimerState::Paused => elapsed,
+        };
+        self.started_at = (state == TimerState::Running).then(|| self.clock.now());
+    }
+
+    /// Update the timer - call this every frame.
+    /// Returns true on the tick where the countdown reaches zero.
+    pub fn tick(&mut self) -> bool {
+        if self.kind != TimerKind::Countdown
+            || self.state != TimerState::Running
+            || self.zero_reached
+            || self.elapsed() < self.total()
+        {
+            return false;
         }
 
-        if let Some(last) = self.last_tick {
-            let elapsed = last.elapsed().as_secs_f32();
-            self.remaining -= elapsed;
-            
-            if self.remaining <= 0.0 {
-                self.remaining = 0.0;
-                self.state = TimerState::Stopped;
-                self.last_tick = None;
-            } else {
-                self.last_tick = Some(Instant::now());
-            }
+        self.zero_reached = true;
+        if !self.overtime {
+            self.accumulated = self.total();
+            self.state = TimerState::Stopped;
+            self.started_at = None;
         }
+        self.events.push(TimerEvent::Finished);
+        true
+    }
+
+    /// Take the transitions since the last call, oldest first
+    pub fn drain_events(&mut self) -> Vec<TimerEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Time spent running since the last reset
+    pub fn elapsed(&self) -> Duration {
+        match self.started_at {
+            Some(started) => self.accumulated + self.clock.now().saturating_duration_since(started),
+            None => self.accumulated,
+        }
+    }
+
+    /// Time left before the countdown reaches zero
+    pub fn remaining(&self) -> Duration {
+        self.total().saturating_sub(self.elapsed())
+    }
+
+    /// Time spent past zero by an overtime countdown
+    pub fn overtime_elapsed(&self) -> Duration {
+        match self.kind {
+            TimerKind::Countdown => self.elapsed().saturating_sub(self.total()),
+            TimerKind::Stopwatch => Duration::ZERO,
+        }
+    }
+
+    /// Whether an overtime countdown is running below zero
+    pub fn is_overtime(&self) -> bool {
+        self.zero_reached && !self.overtime_elapsed().is_zero()
+    }
+
+    /// Share of the countdown already used, from 0.0 to 1.0; always 0.0 for a stopwatch
+    pub fn progress(&self) -> f32 {
+        match self.kind {
+            TimerKind::Countdown if self.duration > 0 => (self.elapsed().as_secs_f32() / self.duration as f32).min(1.0),
+            TimerKind::Countdown => 1.0,
+            TimerKind::Stopwatch => 0.0,
+        }
+    }
+
+    fn total(&self) -> Duration {
+        Duration::from_secs(self.duration as u64)
+    }
+
+    /// Time to show on the display: remaining for a countdown (or time past zero
+    /// in overtime), elapsed for a stopwatch
+    pub fn shown(&self) -> Duration {
+        match self.kind {
+            TimerKind::Countdown if self.is_overtime() => self.overtime_elapsed(),
+            TimerKind::Countdown => self.remaining(),
+            TimerKind::Stopwatch => self.elapsed(),
+        }
+    }
+
+    pub fn hours(&self) -> u32 {
+        (self.shown().as_secs() / 3600) as u32
     }
 
     pub fn minutes(&self) -> u32 {
-        (self.remaining as u32) / 60
+        ((self.shown().as_secs() / 60) % 60) as u32
     }
 
     pub fn seconds(&self) -> u32 {
-        (self.remaining as u32) % 60
+        (self.shown().as_secs() % 60) as u32
     }
 
     pub fn is_running(&self) -> bool {
@@ -123,6 +355,531 @@ impl Timer {
     }
 
     pub fn is_finished(&self) -> bool {
-        self.remaining <= 0.0 && self.state == TimerState::Stopped && self.duration > 0
+        self.kind == TimerKind::Countdown
+            && self.state == TimerState::Stopped
+            && self.duration > 0
+            && self.remaining().is_zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timer(duration: u32) -> (Timer<ManualClock>, ManualClock) {
+        let clock = ManualClock::new();
+        (Timer::with_clock(duration, clock.clone()), clock)
+    }
+
+    #[test]
+    fn new_timer_is_stopped_at_full_duration() {
+        let (timer, _) = timer(90);
+        assert_eq!(timer.state, TimerState::Stopped);
+        assert_eq!(timer.minutes(), 1);
+        assert_eq!(timer.seconds(), 30);
+        assert!(!timer.is_finished());
+    }
+
+    #[test]
+    fn default_timer_is_two_minutes() {
+        let timer = Timer::default();
+        assert_eq!(timer.duration, 120);
+        assert_eq!(timer.remaining(), Duration::from_secs(120));
+    }
+
+    #[test]
+    fn tick_without_start_does_nothing() {
+        let (mut timer, clock) = timer(60);
+        clock.advance_secs(10.0);
+        timer.tick();
+        assert_eq!(timer.remaining(), Duration::from_secs(60));
+        assert_eq!(timer.state, TimerState::Stopped);
+    }
+
+    #[test]
+    fn counts_down_while_running() {
+        let (mut timer, clock) = timer(60);
+        timer.start();
+        assert!(timer.is_running());
+
+        clock.advance_secs(15.0);
+        timer.tick();
+        assert_eq!(timer.seconds(), 45);
+
+        clock.advance_secs(5.0);
+        timer.tick();
+        assert_eq!(timer.seconds(), 40);
+    }
+
+    #[test]
+    fn pause_freezes_remaining_time() {
+        let (mut timer, clock) = timer(60);
+        timer.start();
+        clock.advance_secs(10.0);
+        timer.tick();
+        timer.pause();
+        assert_eq!(timer.state, TimerState::Paused);
+
+        clock.advance_secs(30.0);
+        timer.tick();
+        assert_eq!(timer.seconds(), 50);
+    }
+
+    #[test]
+    fn resume_continues_from_paused_time() {
+        let (mut timer, clock) = timer(60);
+        timer.start();
+        clock.advance_secs(10.0);
+        timer.tick();
+        timer.pause();
+        clock.advance_secs(100.0);
+
+        timer.start();
+        assert!(timer.is_running());
+        clock.advance_secs(5.0);
+        timer.tick();
+        assert_eq!(timer.seconds(), 45);
+    }
+
+    #[test]
+    fn toggle_cycles_through_states() {
+        let (mut timer, _) = timer(60);
+        timer.toggle();
+        assert_eq!(timer.state, TimerState::Running);
+        timer.toggle();
+        assert_eq!(timer.state, TimerState::Paused);
+        timer.toggle();
+        assert_eq!(timer.state, TimerState::Running);
+    }
+
+    #[test]
+    fn pause_when_stopped_is_ignored() {
+        let (mut timer, _) = timer(60);
+        timer.pause();
+        assert_eq!(timer.state, TimerState::Stopped);
+    }
+
+    #[test]
+    fn reset_restores_duration_and_stops() {
+        let (mut timer, clock) = timer(60);
+        timer.start();
+        clock.advance_secs(20.0);
+        timer.tick();
+        timer.reset();
+        assert_eq!(timer.state, TimerState::Stopped);
+        assert_eq!(timer.remaining(), Duration::from_secs(60));
+
+        // Time passing after a reset must not count against the next run
+        clock.advance_secs(20.0);
+        timer.start();
+        timer.tick();
+        assert_eq!(timer.remaining(), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn finishes_when_time_runs_out() {
+        let (mut timer, clock) = timer(30);
+        timer.start();
+        clock.advance_secs(29.0);
+        timer.tick();
+        assert!(!timer.is_finished());
+
+        clock.advance_secs(5.0);
+        timer.tick();
+        assert_eq!(timer.remaining(), Duration::ZERO);
+        assert_eq!(timer.state, TimerState::Stopped);
+        assert!(timer.is_finished());
+    }
+
+    #[test]
+    fn finished_timer_cannot_start_until_reset() {
+        let (mut timer, clock) = timer(5);
+        timer.start();
+        clock.advance_secs(5.0);
+        timer.tick();
+        assert!(timer.is_finished());
+
+        timer.start();
+        assert_eq!(timer.state, TimerState::Stopped);
+
+        timer.reset();
+        timer.start();
+        assert!(timer.is_running());
+    }
+
+    #[test]
+    fn zero_duration_never_runs_or_finishes() {
+        let (mut timer, clock) = timer(0);
+        timer.start();
+        assert_eq!(timer.state, TimerState::Stopped);
+        clock.advance_secs(1.0);
+        timer.tick();
+        assert!(!timer.is_finished());
+    }
+
+    #[test]
+    fn adjusting_duration_while_stopped_updates_remaining() {
+        let (mut timer, _) = timer(60);
+        timer.add_minutes(2);
+        timer.add_seconds(10);
+        assert_eq!(timer.duration, 190);
+        assert_eq!(timer.remaining(), Duration::from_secs(190));
+    }
+
+    #[test]
+    fn adjusting_duration_while_running_keeps_remaining() {
+        let (mut timer, clock) = timer(60);
+        timer.start();
+        clock.advance_secs(10.0);
+        timer.tick();
+        timer.add_minutes(1);
+        assert_eq!(timer.duration, 120);
+        assert_eq!(timer.seconds(), 50);
+    }
+
+    #[test]
+    fn duration_is_clamped() {
+        let (mut timer, _) = timer(30);
+        timer.add_minutes(-5);
+        assert_eq!(timer.duration, 0);
+        timer.add_hours(200);
+        assert_eq!(timer.duration, MAX_DURATION);
+    }
+
+    #[test]
+    fn set_duration_while_paused_keeps_remaining() {
+        let (mut timer, clock) = timer(60);
+        timer.start();
+        clock.advance_secs(10.0);
+        timer.tick();
+        timer.pause();
+        timer.set_duration(300);
+        assert_eq!(timer.duration, 300);
+        assert_eq!(timer.seconds(), 50);
+    }
+
+    #[test]
+    fn remaining_is_derived_without_ticking() {
+        let (mut timer, clock) = timer(60);
+        timer.start();
+        clock.advance(Duration::from_millis(12_500));
+        assert_eq!(timer.remaining(), Duration::from_millis(47_500));
+        assert_eq!(timer.elapsed(), Duration::from_millis(12_500));
+    }
+
+    #[test]
+    fn frequent_ticks_do_not_drift() {
+        let (mut timer, clock) = timer(3600);
+        timer.start();
+        for _ in 0..10_000 {
+            clock.advance(Duration::from_nanos(16_666_667));
+            timer.tick();
+        }
+        assert_eq!(timer.elapsed(), Duration::from_nanos(166_666_670_000));
+        assert_eq!(
+            timer.remaining(),
+            Duration::from_secs(3600) - Duration::from_nanos(166_666_670_000)
+        );
+    }
+
+    #[test]
+    fn pause_and_resume_stretches_add_up_exactly() {
+        let (mut timer, clock) = timer(600);
+        for _ in 0..100 {
+            timer.start();
+            clock.advance(Duration::from_millis(1_234));
+            timer.pause();
+            clock.advance(Duration::from_secs(7));
+        }
+        assert_eq!(timer.elapsed(), Duration::from_millis(123_400));
+    }
+
+    #[test]
+    fn remaining_never_goes_below_zero() {
+        let (mut timer, clock) = timer(10);
+        timer.start();
+        clock.advance_secs(60.0);
+        assert_eq!(timer.remaining(), Duration::ZERO);
+        timer.tick();
+        assert_eq!(timer.elapsed(), Duration::from_secs(10));
+    }
+
+    fn stopwatch() -> (Timer<ManualClock>, ManualClock) {
+        let (mut timer, clock) = timer(60);
+        timer.set_kind(TimerKind::Stopwatch);
+        (timer, clock)
+    }
+
+    #[test]
+    fn stopwatch_starts_at_zero() {
+        let (timer, _) = stopwatch();
+        assert_eq!(timer.shown(), Duration::ZERO);
+        assert_eq!(timer.minutes(), 0);
+        assert_eq!(timer.seconds(), 0);
+    }
+
+    #[test]
+    fn stopwatch_counts_up_past_duration() {
+        let (mut timer, clock) = stopwatch();
+        timer.start();
+        clock.advance_secs(95.0);
+        timer.tick();
+        assert!(timer.is_running());
+        assert!(!timer.is_finished());
+        assert_eq!(timer.minutes(), 1);
+        assert_eq!(timer.seconds(), 35);
+    }
+
+    #[test]
+    fn stopwatch_runs_with_zero_duration() {
+        let (mut timer, clock) = timer(0);
+        timer.set_kind(TimerKind::Stopwatch);
+        timer.start();
+        clock.advance_secs(3.0);
+        timer.tick();
+        assert_eq!(timer.seconds(), 3);
+    }
+
+    #[test]
+    fn stopwatch_pause_resume_and_reset() {
+        let (mut timer, clock) = stopwatch();
+        timer.start();
+        clock.advance_secs(10.0);
+        timer.pause();
+        clock.advance_secs(10.0);
+        timer.start();
+        clock.advance_secs(2.0);
+        assert_eq!(timer.seconds(), 12);
+
+        timer.reset();
+        assert_eq!(timer.state, TimerState::Stopped);
+        assert_eq!(timer.shown(), Duration::ZERO);
+    }
+
+    #[test]
+    fn switching_kind_resets_progress() {
+        let (mut timer, clock) = timer(60);
+        timer.start();
+        clock.advance_secs(20.0);
+        timer.set_kind(TimerKind::Stopwatch);
+        assert_eq!(timer.state, TimerState::Stopped);
+        assert_eq!(timer.shown(), Duration::ZERO);
+
+        timer.set_kind(TimerKind::Countdown);
+        assert_eq!(timer.shown(), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn supports_durations_beyond_an_hour() {
+        let (mut timer, _) = timer(0);
+        timer.add_hours(2);
+        timer.add_minutes(75);
+        timer.add_seconds(30);
+        assert_eq!(timer.duration, 3 * 3600 + 15 * 60 + 30);
+        assert_eq!(timer.hours(), 3);
+        assert_eq!(timer.minutes(), 15);
+        assert_eq!(timer.seconds(), 30);
+    }
+
+    #[test]
+    fn minutes_wrap_at_the_hour() {
+        let (mut timer, clock) = timer(3600);
+        timer.start();
+        clock.advance_secs(1.0);
+        assert_eq!(timer.hours(), 0);
+        assert_eq!(timer.minutes(), 59);
+        assert_eq!(timer.seconds(), 59);
+    }
+
+    #[test]
+    fn can_reach_maximum_duration() {
+        let (mut timer, _) = timer(0);
+        timer.add_hours(99);
+        timer.add_minutes(59);
+        timer.add_seconds(59);
+        assert_eq!(timer.duration, MAX_DURATION);
+        assert_eq!((timer.hours(), timer.minutes(), timer.seconds()), (99, 59, 59));
+    }
+
+    fn overtime_timer(duration: u32) -> (Timer<ManualClock>, ManualClock) {
+        let (mut timer, clock) = timer(duration);
+        timer.overtime = true;
+        (timer, clock)
+    }
+
+    #[test]
+    fn tick_reports_reaching_zero_once() {
+        let (mut timer, clock) = timer(10);
+        timer.start();
+        clock.advance_secs(5.0);
+        assert!(!timer.tick());
+        clock.advance_secs(5.0);
+        assert!(timer.tick());
+        assert!(!timer.tick());
+    }
+
+    #[test]
+    fn overtime_keeps_counting_past_zero() {
+        let (mut timer, clock) = overtime_timer(10);
+        timer.start();
+        clock.advance_secs(10.0);
+        assert!(timer.tick());
+        assert!(timer.is_running());
+        assert!(!timer.is_finished());
+
+        clock.advance_secs(75.0);
+        assert!(!timer.tick());
+        assert!(timer.is_overtime());
+        assert_eq!(timer.shown(), Duration::from_secs(75));
+        assert_eq!((timer.minutes(), timer.seconds()), (1, 15));
+    }
+
+    #[test]
+    fn overtime_can_pause_and_resume() {
+        let (mut timer, clock) = overtime_timer(10);
+        timer.start();
+        clock.advance_secs(15.0);
+        timer.tick();
+        timer.pause();
+        clock.advance_secs(60.0);
+        timer.start();
+        assert!(timer.is_running());
+        clock.advance_secs(5.0);
+        assert!(!timer.tick());
+        assert_eq!(timer.overtime_elapsed(), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn overtime_is_cleared_by_reset() {
+        let (mut timer, clock) = overtime_timer(10);
+        timer.start();
+        clock.advance_secs(30.0);
+        timer.tick();
+        timer.reset();
+        assert!(!timer.is_overtime());
+        assert_eq!(timer.shown(), Duration::from_secs(10));
+
+        timer.start();
+        clock.advance_secs(10.0);
+        assert!(timer.tick());
+    }
+
+    #[test]
+    fn changing_duration_in_overtime_keeps_time_past_zero() {
+        let (mut timer, clock) = overtime_timer(10);
+        timer.start();
+        clock.advance_secs(25.0);
+        timer.tick();
+        timer.set_duration(20);
+        assert_eq!(timer.overtime_elapsed(), Duration::from_secs(15));
+    }
+
+    #[test]
+    fn changing_a_finished_countdown_lets_it_finish_again() {
+        let (mut timer, clock) = timer(10);
+        timer.start();
+        clock.advance_secs(10.0);
+        assert!(timer.tick());
+        timer.add_minutes(1);
+        timer.start();
+        timer.drain_events();
+        clock.advance_secs(70.0);
+        assert!(timer.tick());
+        assert_eq!(timer.drain_events(), [TimerEvent::Finished]);
+        assert_eq!(timer.state, TimerState::Stopped);
+    }
+
+    #[test]
+    fn extend_adds_to_remaining_while_running() {
+        let (mut timer, clock) = timer(60);
+        timer.start();
+        clock.advance_secs(20.0);
+        timer.extend(30);
+        assert_eq!(timer.duration, 90);
+        assert_eq!(timer.remaining(), Duration::from_secs(70));
+
+        timer.extend(-100);
+        assert_eq!(timer.duration, 0);
+        assert!(timer.tick());
+    }
+
+    #[test]
+    fn extend_after_finishing_allows_another_finish() {
+        let (mut timer, clock) = overtime_timer(10);
+        timer.start();
+        clock.advance_secs(15.0);
+        assert!(timer.tick());
+        timer.extend(60);
+        assert!(!timer.is_overtime());
+        assert_eq!(timer.remaining(), Duration::from_secs(55));
+
+        clock.advance_secs(55.0);
+        assert!(timer.tick());
+    }
+
+    #[test]
+    fn progress_tracks_the_countdown() {
+        let (mut timer, clock) = overtime_timer(60);
+        assert_eq!(timer.progress(), 0.0);
+        timer.start();
+        clock.advance_secs(15.0);
+        assert_eq!(timer.progress(), 0.25);
+        clock.advance_secs(60.0);
+        assert_eq!(timer.progress(), 1.0);
+
+        timer.set_kind(TimerKind::Stopwatch);
+        assert_eq!(timer.progress(), 0.0);
+    }
+
+    #[test]
+    fn queues_transitions() {
+        let (mut timer, clock) = timer(60);
+        timer.start();
+        timer.pause();
+        timer.toggle();
+        clock.advance_secs(60.0);
+        assert!(timer.tick());
+        assert!(!timer.tick());
+        timer.reset();
+        assert_eq!(
+            timer.drain_events(),
+            [TimerEvent::Started, TimerEvent::Paused, TimerEvent::Resumed, TimerEvent::Finished, TimerEvent::Reset]
+        );
+        assert!(timer.drain_events().is_empty());
+    }
+
+    #[test]
+    fn ignores_changes_that_change_nothing() {
+        let (mut timer, _clock) = timer(60);
+        timer.pause();
+        timer.reset();
+        timer.set_duration(60);
+        timer.extend(0);
+        assert!(timer.drain_events().is_empty());
+
+        timer.set_duration(90);
+        timer.extend(-30);
+        timer.start();
+        timer.start();
+        assert_eq!(timer.drain_events(), [TimerEvent::DurationChanged, TimerEvent::DurationChanged, TimerEvent::Started]);
+    }
+
+    #[test]
+    fn restore_counts_time_away() {
+        let (mut timer, clock) = timer(60);
+        timer.restore(TimerState::Running, Duration::from_secs(20), Duration::from_secs(30));
+        assert_eq!(timer.remaining(), Duration::from_secs(10));
+        clock.advance_secs(10.0);
+        assert!(timer.tick());
+
+        timer.restore(TimerState::Paused, Duration::from_secs(20), Duration::from_secs(300));
+        assert_eq!(timer.remaining(), Duration::from_secs(40));
+
+        let (mut timer, clock) = overtime_timer(60);
+        timer.restore(TimerState::Running, Duration::from_secs(70), Duration::from_secs(5));
+        clock.advance_secs(1.0);
+        assert!(!timer.tick(), "already finished before the restart");
+        assert_eq!(timer.shown(), Duration::from_secs(16));
+        assert_eq!(timer.drain_events(), []);
     }
 }
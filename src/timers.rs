@@ -0,0 +1,744 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::laps::Laps;
+use crate::pomodoro::{Pomodoro, PomodoroConfig};
+use crate::presets::Preset;
+use crate::sequence::{countdown_second, Sequence, SequenceConfig};
+use crate::timer::{Timer, TimerEvent, TimerKind, MAX_DURATION};
+
+/// What a timer is being used for
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    Countdown,
+    Stopwatch,
+    Pomodoro,
+    Intervals,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 4] = [Mode::Countdown, Mode::Stopwatch, Mode::Pomodoro, Mode::Intervals];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Mode::Countdown => "⏳ Timer",
+            Mode::Stopwatch => "⏱ Stopwatch",
+            Mode::Pomodoro => "🍅 Pomodoro",
+            Mode::Intervals => "💪 Intervals",
+        }
+    }
+}
+
+/// A timer with a name and its own mode state
+pub struct NamedTimer {
+    /// Identifies the timer within its list while the app runs
+    pub id: u64,
+    pub name: String,
+    pub timer: Timer,
+    pub laps: Laps,
+    pub lap_status: Option<String>,
+    pub pomodoro: Option<Pomodoro>,
+    pub sequence: Option<Sequence>,
+    /// Preset the countdown was loaded from
+    pub preset: Option<String>,
+    /// Message for the "Timer Complete" window
+    pub finished_notice: Option<String>,
+    /// Seconds added to (or taken from) the countdown since it was set; a reset undoes them
+    pub extended: i32,
+    /// Last second of a segment announced with `CountdownSecond`
+    last_countdown_second: Option<u64>,
+    /// Whether the countdown was inside the warning time last frame
+    warned: bool,
+    /// Events not yet drained, besides those still queued on the timer
+    events: Vec<TimerEvent>,
+}
+
+impl NamedTimer {
+    pub fn new(name: &str) -> Self {
+        Self {
+            id: 0,
+            name: name.to_owned(),
+            timer: Timer::default(),
+            laps: Laps::default(),
+            lap_status: None,
+            pomodoro: None,
+            sequence: None,
+            preset: None,
+            finished_notice: None,
+            extended: 0,
+            last_countdown_second: None,
+            warned: false,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        match (&self.pomodoro, &self.sequence, self.timer.kind) {
+            (Some(_), _, _) => Mode::Pomodoro,
+            (_, Some(_), _) => Mode::Intervals,
+            (None, None, TimerKind::Countdown) => Mode::Countdown,
+            (None, None, TimerKind::Stopwatch) => Mode::Stopwatch,
+        }
+    }
+
+    pub fn set_mode(&mut self, mode: Mode, pomodoro: &PomodoroConfig, sequence: &SequenceConfig) {
+        self.pomodoro = None;
+        self.sequence = None;
+        self.preset = None;
+        self.extended = 0;
+        match mode {
+            Mode::Countdown => self.timer.set_kind(TimerKind::Countdown),
+            Mode::Stopwatch => self.timer.set_kind(TimerKind::Stopwatch),
+            Mode::Pomodoro => {
+                let pomodoro = Pomodoro::new(pomodoro.clone());
+                pomodoro.apply(&mut self.timer);
+                self.pomodoro = Some(pomodoro);
+            }
+            Mode::Intervals => self.sequence = Some(Sequence::new(sequence.clone())),
+        }
+        self.reset();
+    }
+
+    /// Switch to a stopped countdown of the given length
+    pub fn set_countdown(&mut self, seconds: u32) {
+        self.pomodoro = None;
+        self.sequence = None;
+        self.preset = None;
+        self.extended = 0;
+        self.timer.set_kind(TimerKind::Countdown);
+        self.reset();
+        self.timer.set_duration(seconds);
+    }
+
+    pub fn load_preset(&mut self, preset: &Preset) {
+        self.set_countdown(preset.seconds);
+        self.preset = Some(preset.name.clone());
+    }
+
+    /// Advance the timer - call this every frame
+    pub fn update(&mut self) {
+        if self.timer.tick() {
+            self.on_finished();
+        }
+        self.count_down_last_seconds();
+    }
+
+    /// Take everything that happened to this timer since the last call, oldest first
+    pub fn drain_events(&mut self) -> Vec<TimerEvent> {
+        let mut events = std::mem::take(&mut self.events);
+        events.extend(self.timer.drain_events());
+        events
+    }
+
+    /// Move to another phase, reporting it as `PhaseChanged` rather than the
+    /// reset and new duration it takes to load the phase
+    fn change_phase<R>(&mut self, change: impl FnOnce(&mut Self) -> R) -> R {
+        self.events.extend(self.timer.drain_events());
+        // The next phase brings its own length
+        self.extended = 0;
+        let before = self.phase_label();
+        let result = change(self);
+        if self.phase_label() != before {
+            self.events.push(TimerEvent::PhaseChanged);
+        }
+        let loading = |event: &TimerEvent| matches!(event, TimerEvent::Reset | TimerEvent::DurationChanged);
+        self.events.extend(self.timer.drain_events().into_iter().filter(|event| !loading(event)));
+        result
+    }
+
+    /// React to the countdown reaching zero
+    fn on_finished(&mut self) {
+        if self.sequence.is_some() {
+            self.events.push(TimerEvent::SegmentEnded);
+            let start = self.events.len();
+            let more = self.change_phase(|entry| entry.sequence.as_mut().is_some_and(|s| s.advance(&mut entry.timer)));
+            if more {
+                // Only the end of the whole sequence counts as finished
+                let segment = self.events.split_off(start);
+                self.events.extend(segment.into_iter().filter(|event| *event != TimerEvent::Finished));
+            } else {
+                self.finished_notice = Some("💪 Intervals complete!".to_owned());
+            }
+            return;
+        }
+
+        let notice = self.change_phase(|entry| {
+            entry.pomodoro.as_mut().map(|pomodoro| {
+                let done = pomodoro.phase();
+                let next = pomodoro.advance(&mut entry.timer);
+                format!("🍅 {} done! Next: {}", done.label(), next.label())
+            })
+        });
+        self.finished_notice = Some(notice.unwrap_or_else(|| "⏰ Time's up!".to_owned()));
+    }
+
+    /// Queue `CountdownSecond` once per second during the last three seconds of an interval
+    fn count_down_last_seconds(&mut self) {
+        let second = match &self.sequence {
+            Some(_) if self.timer.is_running() => countdown_second(self.timer.remaining()),
+            _ => None,
+        };
+        if second.is_some() && second != self.last_countdown_second {
+            self.events.push(TimerEvent::CountdownSecond);
+        }
+        self.last_countdown_second = second;
+    }
+
+    /// Move the Pomodoro cycle on without waiting for the countdown
+    pub fn skip_phase(&mut self) {
+        self.change_phase(|entry| {
+            if let Some(pomodoro) = entry.pomodoro.as_mut() {
+                pomodoro.advance(&mut entry.timer);
+            }
+        });
+    }
+
+    /// Whether a running countdown just came within `warning` of zero; interval
+    /// timers have their own beeps and never warn
+    pub fn take_warning(&mut self, warning: Duration) -> bool {
+        let remaining = self.timer.remaining();
+        let within = !warning.is_zero()
+            && self.sequence.is_none()
+            && self.timer.kind == TimerKind::Countdown
+            && self.timer.is_running()
+            && !remaining.is_zero()
+            && remaining <= warning;
+        let due = within && !self.warned;
+        self.warned = within;
+        due
+    }
+
+    /// Close the finished message; a countdown that stopped at zero is reset
+    pub fn dismiss(&mut self) {
+        self.finished_notice = None;
+        // An overtime countdown keeps running after the notice
+        if self.timer.is_finished() {
+            self.reset();
+        }
+    }
+
+    /// Whether the finished message can offer snooze and restart, which only
+    /// make sense for a plain countdown
+    pub fn can_snooze(&self) -> bool {
+        self.mode() == Mode::Countdown
+    }
+
+    /// Run the countdown again from the top, at the length it was set to
+    pub fn restart(&mut self) {
+        self.reset();
+        self.timer.start();
+    }
+
+    /// Add time to (or take it from) a countdown without stopping it
+    pub fn extend(&mut self, seconds: i32) {
+        if self.timer.kind != TimerKind::Countdown {
+            return;
+        }
+        let before = self.timer.duration;
+        self.timer.extend(seconds);
+        self.extended += self.timer.duration as i32 - before as i32;
+    }
+
+    /// Have a finished countdown end again `seconds` from now
+    pub fn snooze(&mut self, seconds: u32) {
+        self.finished_notice = None;
+        // Make up the time an overtime countdown has run past zero
+        let over = self.timer.overtime_elapsed().as_secs_f32().ceil() as u32;
+        self.extend(seconds.saturating_add(over).min(MAX_DURATION) as i32);
+        self.timer.start();
+    }
+
+    pub fn record_lap(&mut self) {
+        if self.timer.kind == TimerKind::Stopwatch && self.timer.is_running() {
+            self.laps.record(self.timer.elapsed());
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.timer.reset();
+        if self.extended != 0 {
+            let duration = self.timer.duration as i64 - self.extended as i64;
+            self.timer.set_duration(duration.clamp(0, MAX_DURATION as i64) as u32);
+            self.extended = 0;
+        }
+        if let Some(sequence) = self.sequence.as_mut() {
+            *sequence = Sequence::new(sequence.config.clone());
+            sequence.apply(&mut self.timer);
+        }
+        self.last_countdown_second = None;
+        self.finished_notice = None;
+        self.laps.clear();
+        self.lap_status = None;
+    }
+
+    /// Label for the display, e.g. "Work 2/4"
+    pub fn phase_label(&self) -> Option<String> {
+        self.pomodoro
+            .as_ref()
+            .map(Pomodoro::status)
+            .or_else(|| self.sequence.as_ref().map(Sequence::status))
+    }
+}
+
+/// How new timers start out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimerDefaults {
+    /// Countdown length in seconds
+    pub duration: u32,
+    pub overtime: bool,
+}
+
+impl Default for TimerDefaults {
+    fn default() -> Self {
+        Self {
+            duration: 120,
+            overtime: false,
+        }
+    }
+}
+
+/// Independently running timers, one of which is shown large
+pub struct TimerList {
+    timers: Vec<NamedTimer>,
+    primary: usize,
+    defaults: TimerDefaults,
+    /// Id for the next timer added
+    next_id: u64,
+}
+
+impl Default for TimerList {
+    fn default() -> Self {
+        Self::new(TimerDefaults::default())
+    }
+}
+
+impl TimerList {
+    pub fn new(defaults: TimerDefaults) -> Self {
+        let mut list = Self {
+            timers: Vec::new(),
+            primary: 0,
+            defaults,
+            next_id: 1,
+        };
+        let first = list.new_timer("Timer 1");
+        list.timers.push(first);
+        list
+    }
+
+    /// A list of existing timers, e.g. restored from the last session; empty
+    /// gives a single new timer
+    pub fn from_timers(defaults: TimerDefaults, timers: Vec<NamedTimer>, primary: usize) -> Self {
+        if timers.is_empty() {
+            return Self::new(defaults);
+        }
+        let primary = primary.min(timers.len() - 1);
+        let mut list = Self {
+            timers: Vec::new(),
+            primary,
+            defaults,
+            next_id: 1,
+        };
+        for mut entry in timers {
+            entry.id = list.take_id();
+            list.timers.push(entry);
+        }
+        list
+    }
+
+    /// Settings for timers added from now on
+    pub fn set_defaults(&mut self, defaults: TimerDefaults) {
+        self.defaults = defaults;
+    }
+
+    fn take_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id - 1
+    }
+
+    fn new_timer(&mut self, name: &str) -> NamedTimer {
+        let mut entry = NamedTimer::new(name);
+        entry.id = self.take_id();
+        entry.timer = Timer::new(self.defaults.duration);
+        entry.timer.overtime = self.defaults.overtime;
+        entry
+    }
+
+    pub fn len(&self) -> usize {
+        self.timers.len()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&NamedTimer> {
+        self.timers.get(index)
+    }
+
+    pub fn find_mut(&mut self, id: u64) -> Option<&mut NamedTimer> {
+        self.timers.iter_mut().find(|entry| entry.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &NamedTimer> {
+        self.timers.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut NamedTimer> {
+        self.timers.iter_mut()
+    }
+
+    pub fn primary_index(&self) -> usize {
+        self.primary
+    }
+
+    pub fn primary(&self) -> &NamedTimer {
+        &self.timers[self.primary]
+    }
+
+    pub fn primary_mut(&mut self) -> &mut NamedTimer {
+        &mut self.timers[self.primary]
+    }
+
+    pub fn select(&mut self, index: usize) {
+        if index < self.timers.len() {
+            self.primary = index;
+        }
+    }
+
+    /// Add a timer with the next free "Timer N" name and make it primary
+    pub fn add(&mut self) -> usize {
+        let name = (1..)
+            .map(|n| format!("Timer {n}"))
+            .find(|name| self.timers.iter().all(|t| &t.name != name))
+            .unwrap_or_default();
+        let entry = self.new_timer(&name);
+        self.timers.push(entry);
+        self.primary = self.timers.len() - 1;
+        self.primary
+    }
+
+    /// Remove a timer; the last remaining timer is kept
+    pub fn remove(&mut self, index: usize) {
+        if self.timers.len() <= 1 || index >= self.timers.len() {
+            return;
+        }
+        self.timers.remove(index);
+        if self.primary > index || self.primary >= self.timers.len() {
+            self.primary = self.primary.saturating_sub(1);
+        }
+    }
+
+    pub fn rename(&mut self, index: usize, name: &str) {
+        let name = name.trim();
+        if let (Some(timer), false) = (self.timers.get_mut(index), name.is_empty()) {
+            timer.name = name.to_owned();
+        }
+    }
+
+    /// Move a timer to a new position, keeping the same primary timer selected
+    pub fn move_timer(&mut self, from: usize, to: usize) {
+        if from >= self.timers.len() || to >= self.timers.len() || from == to {
+            return;
+        }
+        let timer = self.timers.remove(from);
+        self.timers.insert(to, timer);
+
+        self.primary = if self.primary == from {
+            to
+        } else if from < self.primary && self.primary <= to {
+            self.primary - 1
+        } else if to <= self.primary && self.primary < from {
+            self.primary + 1
+        } else {
+            self.primary
+        };
+    }
+
+    pub fn any_running(&self) -> bool {
+        self.timers.iter().any(|t| t.timer.is_running())
+    }
+
+    /// Advance every timer and collect what happened to each since the last
+    /// call, as `(index, event)` pairs
+    pub fn update_all(&mut self) -> Vec<(usize, TimerEvent)> {
+        let mut events = Vec::new();
+        for (index, timer) in self.timers.iter_mut().enumerate() {
+            timer.update();
+            events.extend(timer.drain_events().into_iter().map(|event| (index, event)));
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::TimerState;
+
+    fn names(list: &TimerList) -> Vec<&str> {
+        list.timers.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    fn list_of(n: usize) -> TimerList {
+        let mut list = TimerList::default();
+        for _ in 1..n {
+            list.add();
+        }
+        list
+    }
+
+    #[test]
+    fn starts_with_one_timer() {
+        let list = TimerList::default();
+        assert_eq!(names(&list), ["Timer 1"]);
+        assert_eq!(list.primary_index(), 0);
+    }
+
+    #[test]
+    fn new_timers_use_defaults() {
+        let mut list = TimerList::new(TimerDefaults { duration: 300, overtime: true });
+        assert_eq!(list.primary().timer.duration, 300);
+
+        list.set_defaults(TimerDefaults { duration: 60, overtime: false });
+        list.add();
+        assert_eq!(list.primary().timer.duration, 60);
+        assert!(!list.primary().timer.overtime);
+        assert!(list.update_all().is_empty());
+    }
+
+    #[test]
+    fn timers_keep_their_ids() {
+        let mut list = list_of(3);
+        let ids: Vec<u64> = list.iter().map(|entry| entry.id).collect();
+        assert_eq!(ids, [1, 2, 3]);
+        list.move_timer(0, 2);
+        list.remove(0);
+        assert_eq!(list.add(), 2);
+        assert_eq!(list.primary().id, 4);
+        assert_eq!(list.find_mut(1).map(|entry| entry.name.clone()).as_deref(), Some("Timer 1"));
+        assert!(list.find_mut(2).is_none());
+    }
+
+    #[test]
+    fn snooze_and_restart_finished_countdowns() {
+        let mut entry = NamedTimer::new("Tea");
+        entry.set_countdown(60);
+        entry.timer.restore(TimerState::Stopped, Duration::from_secs(60), Duration::ZERO);
+        entry.finished_notice = Some("⏰ Time's up!".to_owned());
+
+        entry.snooze(300);
+        assert_eq!(entry.finished_notice, None);
+        assert!(entry.timer.is_running());
+        assert!((299..=300).contains(&entry.timer.remaining().as_secs()));
+
+        entry.restart();
+        assert!(entry.timer.is_running());
+        assert_eq!(entry.timer.duration, 60, "restart keeps the length that was set");
+    }
+
+    #[test]
+    fn snooze_makes_up_overtime() {
+        let mut entry = NamedTimer::new("Tea");
+        entry.set_countdown(60);
+        entry.timer.overtime = true;
+        entry.timer.restore(TimerState::Running, Duration::from_secs(90), Duration::ZERO);
+        entry.snooze(60);
+        assert!((59..=60).contains(&entry.timer.remaining().as_secs()));
+        assert_eq!(entry.extended, 90);
+    }
+
+    #[test]
+    fn extend_keeps_running_until_reset() {
+        let mut entry = NamedTimer::new("Tea");
+        entry.set_countdown(300);
+        entry.timer.start();
+        entry.extend(60);
+        entry.extend(120);
+        entry.extend(-60);
+        assert!(entry.timer.is_running());
+        assert_eq!(entry.timer.duration, 420);
+        assert!((419..=420).contains(&entry.timer.remaining().as_secs()));
+
+        entry.reset();
+        assert_eq!((entry.timer.duration, entry.extended), (300, 0));
+
+        entry.set_mode(Mode::Stopwatch, &PomodoroConfig::default(), &SequenceConfig::default());
+        entry.extend(60);
+        assert_eq!(entry.extended, 0);
+        entry.set_mode(Mode::Pomodoro, &PomodoroConfig::default(), &SequenceConfig::default());
+        assert!(!entry.can_snooze());
+        entry.extend(60);
+        entry.skip_phase();
+        assert_eq!((entry.timer.duration, entry.extended), (PomodoroConfig::default().short_break, 0));
+    }
+
+    #[test]
+    fn add_uses_free_name_and_selects_it() {
+        let mut list = list_of(3);
+        list.remove(1);
+        assert_eq!(list.add(), 2);
+        assert_eq!(names(&list), ["Timer 1", "Timer 3", "Timer 2"]);
+        assert_eq!(list.primary().name, "Timer 2");
+    }
+
+    #[test]
+    fn keeps_last_timer() {
+        let mut list = TimerList::default();
+        list.remove(0);
+        assert_eq!(list.len(), 1);
+    }
+
+    #[test]
+    fn remove_keeps_primary_selection() {
+        let mut list = list_of(3);
+        list.select(2);
+        list.remove(0);
+        assert_eq!(list.primary().name, "Timer 3");
+
+        list.remove(1);
+        assert_eq!(list.primary().name, "Timer 2");
+    }
+
+    #[test]
+    fn rename_ignores_blank_names() {
+        let mut list = TimerList::default();
+        list.rename(0, "  Tea ");
+        assert_eq!(list.primary().name, "Tea");
+        list.rename(0, "   ");
+        assert_eq!(list.primary().name, "Tea");
+    }
+
+    #[test]
+    fn move_keeps_primary_selection() {
+        let mut list = list_of(4);
+        list.select(1);
+        list.move_timer(0, 3);
+        assert_eq!(names(&list), ["Timer 2", "Timer 3", "Timer 4", "Timer 1"]);
+        assert_eq!(list.primary().name, "Timer 2");
+
+        list.move_timer(3, 0);
+        assert_eq!(list.primary().name, "Timer 2");
+
+        list.move_timer(1, 2);
+        assert_eq!(list.primary_index(), 2);
+    }
+
+    #[test]
+    fn timers_run_independently() {
+        let mut list = list_of(2);
+        list.primary_mut().timer.start();
+        list.select(0);
+        assert!(list.any_running());
+        assert!(!list.primary().timer.is_running());
+    }
+
+    #[test]
+    fn update_all_collects_events() {
+        let mut list = list_of(2);
+        list.rename(1, "Tea");
+        let tea = list.primary_mut();
+        tea.set_countdown(5);
+        tea.timer.start();
+        tea.timer.extend(-5);
+        assert_eq!(
+            list.update_all(),
+            [(1, TimerEvent::DurationChanged), (1, TimerEvent::Started), (1, TimerEvent::DurationChanged), (1, TimerEvent::Finished)]
+        );
+        assert!(list.update_all().is_empty());
+    }
+
+    #[test]
+    fn phase_changes_replace_loading_events() {
+        let mut entry = NamedTimer::new("Focus");
+        entry.set_mode(Mode::Pomodoro, &PomodoroConfig::default(), &SequenceConfig::default());
+        entry.timer.start();
+        entry.drain_events();
+
+        entry.skip_phase();
+        assert_eq!(entry.drain_events(), [TimerEvent::PhaseChanged, TimerEvent::Started]);
+        assert_eq!(entry.phase_label().as_deref(), Some("Short break 1/4"));
+    }
+
+    #[test]
+    fn interval_segments_change_phase_without_finishing() {
+        let mut entry = NamedTimer::new("Workout");
+        entry.set_mode(Mode::Intervals, &PomodoroConfig::default(), &SequenceConfig::default());
+        entry.timer.start();
+        entry.drain_events();
+
+        entry.timer.extend(-60);
+        entry.drain_events();
+        entry.update();
+        assert_eq!(entry.drain_events(), [TimerEvent::SegmentEnded, TimerEvent::PhaseChanged, TimerEvent::Started]);
+        assert_eq!(entry.finished_notice, None);
+    }
+
+    #[test]
+    fn counts_down_the_last_seconds_of_a_segment_once_each() {
+        let mut entry = NamedTimer::new("Workout");
+        entry.set_mode(Mode::Intervals, &PomodoroConfig::default(), &SequenceConfig::default());
+        entry.timer.start();
+        entry.drain_events();
+        entry.update();
+        assert!(entry.drain_events().is_empty());
+
+        entry.timer.extend(-58);
+        entry.drain_events();
+        entry.update();
+        entry.update();
+        assert_eq!(entry.drain_events(), [TimerEvent::CountdownSecond]);
+    }
+
+    #[test]
+    fn warns_once_before_the_end() {
+        let mut entry = NamedTimer::new("Tea");
+        entry.set_countdown(10);
+        let warning = Duration::from_secs(30);
+        assert!(!entry.take_warning(warning), "not running");
+        entry.timer.start();
+        assert!(entry.take_warning(warning));
+        assert!(!entry.take_warning(warning));
+        assert!(!entry.take_warning(Duration::ZERO));
+
+        entry.set_mode(Mode::Intervals, &PomodoroConfig::default(), &SequenceConfig::default());
+        entry.timer.start();
+        assert!(!entry.take_warning(Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn dismiss_resets_finished_countdowns() {
+        let mut entry = NamedTimer::new("Tea");
+        entry.set_countdown(60);
+        entry.timer.restore(TimerState::Stopped, Duration::from_secs(60), Duration::ZERO);
+        entry.finished_notice = Some("⏰ Time's up!".to_owned());
+        assert!(entry.timer.is_finished());
+        entry.dismiss();
+        assert_eq!(entry.finished_notice, None);
+        assert_eq!(entry.timer.remaining().as_secs(), 60);
+    }
+
+    #[test]
+    fn modes_are_per_timer() {
+        let mut list = list_of(2);
+        list.primary_mut()
+            .set_mode(Mode::Pomodoro, &PomodoroConfig::default(), &SequenceConfig::default());
+        list.select(0);
+        assert_eq!(list.primary().mode(), Mode::Countdown);
+        list.select(1);
+        assert_eq!(list.primary().mode(), Mode::Pomodoro);
+        assert_eq!(list.primary().phase_label().as_deref(), Some("Work 1/4"));
+    }
+
+    #[test]
+    fn preset_loads_stopped_countdown() {
+        let mut entry = NamedTimer::new("Tea");
+        entry.set_mode(Mode::Pomodoro, &PomodoroConfig::default(), &SequenceConfig::default());
+        entry.timer.start();
+        entry.load_preset(&Preset::new("Tea", 210));
+        assert_eq!(entry.mode(), Mode::Countdown);
+        assert!(!entry.timer.is_running());
+        assert_eq!(entry.timer.remaining().as_secs(), 210);
+        assert_eq!(entry.preset.as_deref(), Some("Tea"));
+
+        entry.set_countdown(60);
+        assert_eq!(entry.preset, None);
+    }
+}
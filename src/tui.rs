@@ -0,0 +1,255 @@
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Color, Print, ResetColor, SetForegroundColor};
+use crossterm::{cursor, execute, queue, terminal};
+
+use crate::cli::Args;
+use crate::display::{text_rows, TimeParts};
+use crate::presets::Presets;
+use crate::timer::{Timer, TimerEvent, TimerKind, TimerState};
+
+/// How often the display is refreshed while waiting for keys
+const FRAME: Duration = Duration::from_millis(100);
+
+/// Pixel styles from largest to smallest
+const PIXELS: [&str; 2] = ["██", "█"];
+
+/// Restores the terminal when dropped, including on panic
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), ResetColor, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Set up the timer from the command line, as the window would
+fn startup_timer(args: &Args) -> io::Result<Timer> {
+    let mut timer = Timer::default();
+    if args.stopwatch {
+        timer.set_kind(TimerKind::Stopwatch);
+    } else if let Some(seconds) = args.duration {
+        timer.set_duration(seconds);
+    } else if let Some(name) = &args.preset {
+        let presets = match Presets::default_path() {
+            Some(path) => Presets::load(&path)?,
+            None => Presets::default(),
+        };
+        let preset = presets
+            .find(name)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no preset named \"{name}\"")))?;
+        timer.set_duration(preset.seconds);
+    }
+    if args.start {
+        timer.start();
+    }
+    Ok(timer)
+}
+
+/// Run the timer in the terminal until the user quits
+pub fn run(args: &Args) -> io::Result<()> {
+    let mut timer = startup_timer(args)?;
+    let title = args.title.clone().unwrap_or_else(|| "Timer".to_owned());
+    let mut finished = false;
+    let mut last_frame = Vec::new();
+
+    let _guard = TerminalGuard::enter()?;
+    let mut stdout = io::stdout();
+
+    loop {
+        timer.tick();
+        for event in timer.drain_events() {
+            match event {
+                TimerEvent::Finished => {
+                    finished = true;
+                    queue!(stdout, Print('\x07'))?;
+                }
+                TimerEvent::Started | TimerEvent::Resumed | TimerEvent::Reset => finished = false,
+                _ => {}
+            }
+        }
+
+        let (width, height) = terminal::size()?;
+        let frame = layout(&timer, &title, finished, width, height);
+        if frame != last_frame {
+            draw(&mut stdout, &frame, timer.is_overtime() || finished)?;
+            last_frame = frame;
+        }
+
+        if !event::poll(FRAME)? {
+            continue;
+        }
+        match event::read()? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => match handle_key(&mut timer, key) {
+                Action::Quit => return Ok(()),
+                Action::Reset => timer.reset(),
+                Action::Toggle => timer.toggle(),
+                Action::None => {}
+            },
+            // Force a full redraw at the new size
+            Event::Resize(..) => last_frame.clear(),
+            _ => {}
+        }
+    }
+}
+
+enum Action {
+    Toggle,
+    Reset,
+    Quit,
+    None,
+}
+
+fn handle_key(timer: &mut Timer, key: KeyEvent) -> Action {
+    let stopped_countdown = timer.kind == TimerKind::Countdown && timer.state == TimerState::Stopped;
+    match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Action::Quit,
+        KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+        KeyCode::Char(' ') | KeyCode::Char('s') | KeyCode::Enter => Action::Toggle,
+        KeyCode::Char('r') => Action::Reset,
+        KeyCode::Up | KeyCode::Char('+') if stopped_countdown => {
+            timer.add_minutes(1);
+            Action::None
+        }
+        KeyCode::Down | KeyCode::Char('-') if stopped_countdown => {
+            timer.add_minutes(-1);
+            Action::None
+        }
+        _ => Action::None,
+    }
+}
+
+/// Lines of the screen, centered in a `width` x `height` terminal
+fn layout(timer: &Timer, title: &str, finished: bool, width: u16, height: u16) -> Vec<String> {
+    let time = TimeParts {
+        negative: timer.is_overtime(),
+        ..TimeParts::from(timer.shown())
+    };
+    // Blink the colon every half second while running
+    let show_colon = !timer.is_running() || timer.shown().subsec_millis() >= 500;
+    let width = width as usize;
+
+    let digits = PIXELS
+        .iter()
+        .map(|pixel| text_rows(time, show_colon, pixel))
+        .find(|rows| rows[0].chars().count() <= width)
+        .unwrap_or_else(|| vec![plain_time(time)]);
+
+    let status = match timer.state {
+        _ if finished => "⏰ Time's up!  r reset · q quit",
+        TimerState::Running => "space pause · r reset · q quit",
+        TimerState::Paused => "space resume · r reset · q quit",
+        TimerState::Stopped if timer.kind == TimerKind::Countdown => "space start · ↑/↓ minutes · q quit",
+        TimerState::Stopped => "space start · q quit",
+    };
+
+    let centered = |line: &str| {
+        let line_width = line.chars().count();
+        format!("{}{line}", " ".repeat(width.saturating_sub(line_width) / 2))
+    };
+
+    // Drop the title and hints when the terminal is too short
+    let show_text = height as usize >= digits.len() + 4;
+    let mut body = Vec::new();
+    if show_text {
+        body.extend([centered(title), String::new()]);
+    }
+    body.append(&mut digits.iter().map(|row| centered(row)).collect());
+    if show_text {
+        body.extend([String::new(), centered(status)]);
+    }
+
+    let top = (height as usize).saturating_sub(body.len()) / 2;
+    let mut screen = vec![String::new(); top];
+    screen.append(&mut body);
+    screen
+}
+
+/// Compact fallback for very narrow terminals, e.g. "-01:07"
+fn plain_time(time: TimeParts) -> String {
+    let sign = if time.negative { "-" } else { "" };
+    if time.hours > 0 {
+        format!("{sign}{:02}:{:02}:{:02}", time.hours, time.minutes, time.seconds)
+    } else {
+        format!("{sign}{:02}:{:02}", time.minutes, time.seconds)
+    }
+}
+
+fn draw(out: &mut impl Write, screen: &[String], alert: bool) -> io::Result<()> {
+    queue!(out, terminal::Clear(terminal::ClearType::All))?;
+    if alert {
+        queue!(out, SetForegroundColor(Color::Red))?;
+    }
+    for (row, line) in screen.iter().enumerate() {
+        queue!(out, cursor::MoveTo(0, row as u16), Print(line))?;
+    }
+    queue!(out, ResetColor)?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timer(seconds: u32) -> Timer {
+        let mut timer = Timer::default();
+        timer.set_duration(seconds);
+        timer
+    }
+
+    #[test]
+    fn uses_largest_pixels_that_fit() {
+        let pixels = |screen: Vec<String>| screen.concat().matches('█').count();
+        let wide = pixels(layout(&timer(90), "Tea", false, 120, 40));
+        let narrow = pixels(layout(&timer(90), "Tea", false, 40, 40));
+        assert!(narrow > 0);
+        assert_eq!(wide, 2 * narrow);
+
+        let tiny = layout(&timer(90), "Tea", false, 10, 40);
+        assert!(tiny.iter().any(|line| line.trim() == "01:30"));
+    }
+
+    #[test]
+    fn centers_the_display() {
+        let screen = layout(&timer(90), "Tea", false, 120, 40);
+        let rows: Vec<_> = screen.iter().filter(|line| line.contains('█')).collect();
+        assert_eq!(rows.len(), 7);
+        assert_eq!(screen.len(), (40 - 11) / 2 + 11);
+        assert_eq!(screen[(40 - 11) / 2].trim(), "Tea");
+    }
+
+    #[test]
+    fn short_terminal_hides_title_and_hints() {
+        let screen = layout(&timer(90), "Tea", false, 120, 8);
+        assert_eq!(screen.len(), 7);
+        assert!(screen.iter().all(|line| line.contains('█')));
+    }
+
+    #[test]
+    fn arrow_keys_adjust_stopped_countdown() {
+        let mut timer = timer(90);
+        handle_key(&mut timer, KeyEvent::from(KeyCode::Up));
+        assert_eq!(timer.duration, 150);
+
+        timer.start();
+        handle_key(&mut timer, KeyEvent::from(KeyCode::Down));
+        assert_eq!(timer.duration, 150);
+    }
+
+    #[test]
+    fn plain_time_fallback() {
+        assert_eq!(plain_time(TimeParts { negative: true, hours: 0, minutes: 1, seconds: 7 }), "-01:07");
+        assert_eq!(plain_time(TimeParts { negative: false, hours: 2, minutes: 0, seconds: 5 }), "02:00:05");
+    }
+}